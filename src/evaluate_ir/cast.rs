@@ -0,0 +1,90 @@
+use llvm_sys::LLVMOpcode::*;
+use llvm_sys::*;
+
+use parser::expressions::*;
+
+use evaluate_ir::object::*;
+
+use ir::converter::*;
+use ir::llvm_type::*;
+
+pub fn calculate_cast(
+    builder: *mut LLVMBuilder,
+    object: Object,
+    expression_type: LLVMExpressionType,
+    location: Location,
+) -> Object {
+    match (object, expression_type) {
+        (Object::Integer(from, value), LLVMExpressionType::Integer(to)) => {
+            Object::Integer(to, cast_integer(builder, from, to, value))
+        }
+        (Object::Integer(from, value), LLVMExpressionType::Float) => {
+            let opcode = if from.is_signed() {
+                LLVMSIToFP
+            } else {
+                LLVMUIToFP
+            };
+            Object::Float(build_cast(builder, opcode, value, double_type(), ""))
+        }
+        (Object::Float(value), LLVMExpressionType::Integer(to)) => {
+            let opcode = if to.is_signed() {
+                LLVMFPToSI
+            } else {
+                LLVMFPToUI
+            };
+            let llvm_type = convert_integer_type(to);
+            Object::Integer(to, build_cast(builder, opcode, value, llvm_type, ""))
+        }
+        (Object::Float(value), LLVMExpressionType::Float) => Object::Float(value),
+        (Object::Boolean(value), LLVMExpressionType::Integer(to)) => {
+            let llvm_type = convert_integer_type(to);
+            Object::Integer(to, build_cast(builder, LLVMZExt, value, llvm_type, ""))
+        }
+        (Object::Error(message), _) => Object::Error(message),
+        (object, expression_type) => Object::Error(format!(
+            "{} cannot be cast as {}. row: {}, column: {}",
+            object, expression_type, location.row, location.column
+        )),
+    }
+}
+
+pub fn cast_integer(
+    builder: *mut LLVMBuilder,
+    from: IntegerType,
+    to: IntegerType,
+    value: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let opcode = if to.bit_width() == from.bit_width() {
+        return value;
+    } else if to.bit_width() < from.bit_width() {
+        LLVMTrunc
+    } else if from.is_signed() {
+        LLVMSExt
+    } else {
+        LLVMZExt
+    };
+    build_cast(builder, opcode, value, convert_integer_type(to), "")
+}
+
+// integers of different types cannot be mixed without an explicit cast
+pub fn check_integer_type(
+    object: Object,
+    expected_type: &LLVMExpressionType,
+    location: &Location,
+) -> Object {
+    match (&object, expected_type) {
+        (Object::Integer(actual, _), LLVMExpressionType::Integer(expected)) => {
+            if actual != expected {
+                return Object::Error(format!(
+                    "mismatched types: expected {}, but actually {}. row: {}, column: {}",
+                    expected_type,
+                    LLVMExpressionType::Integer(*actual),
+                    location.row,
+                    location.column
+                ));
+            }
+            object
+        }
+        _ => object,
+    }
+}
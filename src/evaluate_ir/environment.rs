@@ -6,6 +6,7 @@ use parser::expressions::*;
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub store: HashMap<String, Object>,
+    pub mutabilities: HashMap<String, Mutability>,
 }
 
 impl Environment {
@@ -14,20 +15,68 @@ impl Environment {
         store.insert("null".to_string(), Object::Null);
         store.insert("void".to_string(), Object::Null);
         store.insert("printf".to_string(), Object::BuildIn(BuildIn::Printf));
+        store.insert("print".to_string(), Object::BuildIn(BuildIn::Print));
+        store.insert("println".to_string(), Object::BuildIn(BuildIn::Println));
+        store.insert("read_line".to_string(), Object::BuildIn(BuildIn::ReadLine));
+        store.insert("read_int".to_string(), Object::BuildIn(BuildIn::ReadInt));
+        store.insert("read_ok".to_string(), Object::BuildIn(BuildIn::ReadOk));
+        store.insert("open".to_string(), Object::BuildIn(BuildIn::Open));
+        store.insert("read_all".to_string(), Object::BuildIn(BuildIn::ReadAll));
+        store.insert("write".to_string(), Object::BuildIn(BuildIn::Write));
+        store.insert("close".to_string(), Object::BuildIn(BuildIn::Close));
+        store.insert("exists".to_string(), Object::BuildIn(BuildIn::Exists));
+        store.insert("argc".to_string(), Object::BuildIn(BuildIn::Argc));
+        store.insert("argv".to_string(), Object::BuildIn(BuildIn::Argv));
+        store.insert("getenv".to_string(), Object::BuildIn(BuildIn::Getenv));
+        store.insert("exit".to_string(), Object::BuildIn(BuildIn::Exit));
+        store.insert("assert".to_string(), Object::BuildIn(BuildIn::Assert));
+        store.insert("panic".to_string(), Object::BuildIn(BuildIn::Panic));
+        store.insert("abs".to_string(), Object::BuildIn(BuildIn::Abs));
+        store.insert("min".to_string(), Object::BuildIn(BuildIn::Min));
+        store.insert("max".to_string(), Object::BuildIn(BuildIn::Max));
+        store.insert("pow".to_string(), Object::BuildIn(BuildIn::Pow));
+        store.insert("sqrt".to_string(), Object::BuildIn(BuildIn::Sqrt));
+        store.insert("seed".to_string(), Object::BuildIn(BuildIn::Seed));
+        store.insert("rand".to_string(), Object::BuildIn(BuildIn::Rand));
+        store.insert(
+            "rand_range".to_string(),
+            Object::BuildIn(BuildIn::RandRange),
+        );
+        store.insert("clock_ms".to_string(), Object::BuildIn(BuildIn::ClockMs));
         store.insert("length".to_string(), Object::BuildIn(BuildIn::Length));
+        store.insert("len".to_string(), Object::BuildIn(BuildIn::Len));
+        store.insert("to_float".to_string(), Object::BuildIn(BuildIn::ToFloat));
+        store.insert("to_int".to_string(), Object::BuildIn(BuildIn::ToInt));
 
-        Environment { store: store }
+        Environment {
+            store: store,
+            mutabilities: HashMap::new(),
+        }
     }
 
     pub fn get(&self, name: &str, location: Location) -> Object {
         if let Some(obj) = self.store.get(name) {
             return obj.clone();
         };
-        Object::Error(format!("{} is not found. row: {}", name, location.row))
+        Object::Error(format!(
+            "{} is not found. row: {}, column: {}",
+            name, location.row, location.column
+        ))
     }
 
     pub fn set(&mut self, name: String, value: Object) -> Object {
         self.store.insert(name, value.clone());
         value
     }
+
+    pub fn set_mutability(&mut self, name: String, mutability: Mutability) {
+        self.mutabilities.insert(name, mutability);
+    }
+
+    pub fn is_mutable(&self, name: &str) -> bool {
+        match self.mutabilities.get(name) {
+            Some(Mutability::Mutable) => true,
+            _ => false,
+        }
+    }
 }
@@ -8,30 +8,51 @@ use parser::parser::*;
 use parser::prefix::*;
 use parser::statements::*;
 
+use evaluate_ir::cast::*;
 use evaluate_ir::environment::*;
+use evaluate_ir::format::*;
+use evaluate_ir::header::*;
 use evaluate_ir::infix::*;
 use evaluate_ir::object::*;
 use evaluate_ir::prefix::*;
 use evaluate_ir::stack::*;
 
+use ir::arithmetic::*;
 use ir::block::*;
+use ir::check::*;
 use ir::condition::*;
 use ir::const_value::*;
 use ir::converter::*;
 use ir::creator::*;
+use ir::file::*;
 use ir::function::*;
+use ir::input::*;
 use ir::llvm_type::*;
+use ir::math::*;
 use ir::operate::*;
+use ir::optimize::*;
+use ir::process::*;
+use ir::random::*;
+use ir::scope::*;
 use ir::string::*;
 use ir::test_util::*;
 use ir::validate::*;
 
+#[allow(unused_imports)]
+use output::*;
+
 pub struct Eval {
     pub stack_arg: Vec<Vec<Expression>>,
     pub error_stack: Vec<Object>,
     pub lc: LLVMCreator,
     pub main_block: *mut LLVMBasicBlock,
     pub function_stack: FunctionStack,
+    // inserts runtime checks for array bounds, division by zero and integer overflow
+    pub checked: bool,
+    // reported by runtime checks
+    pub file_name: String,
+    // functions defined by `export fn`, which go to the generated header
+    pub exports: Vec<ExportedFunction>,
 }
 
 #[allow(dead_code)]
@@ -46,6 +67,9 @@ impl Eval {
             lc: lc,
             main_block: main_block,
             function_stack: FunctionStack::new(main_function),
+            checked: false,
+            file_name: "input.mr".to_string(),
+            exports: Vec::new(),
         }
     }
 
@@ -78,30 +102,42 @@ impl Eval {
         env: &mut Environment,
     ) -> Option<Object> {
         match statement {
-            Statement::Assignment(ident, expr) => {
-                let obj = self.eval_assign_statement(ident, expr, env);
+            Statement::Assignment(ident, expr, location) => {
+                let obj = self.eval_assign_statement(ident, expr, env, location);
                 let _ = self.accumultae_error(obj);
                 None
             }
-            Statement::AssignmentAggregate(ident, assign_exp, index_expr) => {
-                let obj = self.eval_assign_aggregate_statement(ident, index_expr, assign_exp, env);
+            Statement::AssignmentAggregate(ident, assign_exp, index_expr, location) => {
+                let obj = self
+                    .eval_assign_aggregate_statement(ident, index_expr, assign_exp, env, location);
                 let _ = self.accumultae_error(obj);
                 None
             }
-            Statement::Let(ident, expr_type, expr) => {
+            Statement::Let(ident, mutability, expr_type, expr) => {
+                env.set_mutability(ident.0.to_string(), mutability);
                 let obj = self.eval_let_statement(ident, expr_type, expr, env);
                 let _ = self.accumultae_error(obj);
                 None
             }
             Statement::Expression(expr) => self.eval_expression_statement(expr, env),
-            Statement::Return(expr) => {
-                let obj = self.eval_return_statement(expr, env);
+            Statement::Return(expr, location) => {
+                let obj = self.eval_return_statement(expr, env, location);
                 self.accumultae_error(obj)
             }
             Statement::While(expr, block) => {
                 self.eval_while_statement(expr, block, env);
                 None
             }
+            Statement::Extern(extern_function) => {
+                let obj = self.eval_extern_statement(extern_function, env);
+                let _ = self.accumultae_error(obj);
+                None
+            }
+            Statement::Export(ident, expr, location) => {
+                let obj = self.eval_export_statement(ident, expr, env, location);
+                let _ = self.accumultae_error(obj);
+                None
+            }
         }
     }
 
@@ -110,17 +146,37 @@ impl Eval {
         ident: Identifier,
         expr: Expression,
         env: &mut Environment,
+        location: Location,
     ) -> Object {
-        let identify_object = env.get(&ident.0, Location::new(0)); // TODO
+        let identify_object = env.get(&ident.0, location.clone());
+        if let Err(err) = self.check_assignable(&identify_object, &ident, env, &location) {
+            return err;
+        }
+
+        let expression_type = get_expression_type_from_object(&identify_object);
         let llvm_value_ref = match identify_object {
-            Object::Integer(reference) => reference,
+            Object::Integer(_, reference) => reference,
+            Object::Float(reference) => reference,
             Object::Boolean(reference) => reference,
+            Object::String(reference) => reference,
             Object::Array(_, value, _) => value,
-            _ => 0 as *mut LLVMValue,
+            _ => {
+                return Object::Error(format!(
+                    "{} cannot be reassigned. row: {}, column: {}",
+                    ident.0, location.row, location.column
+                ));
+            }
         };
 
-        let mut object = self.eval_expression(expr, &mut env.clone());
-        let llvm_value = unwrap_object(&mut object);
+        let object = self.eval_expression_with_hint(expr, &expression_type, &mut env.clone());
+        let mut object = check_integer_type(object, &expression_type, &location);
+        if let Object::Error(_) = object {
+            return object;
+        }
+        let mut llvm_value = unwrap_object(&mut object);
+        if let Object::Array(_, _, _) = object {
+            llvm_value = build_load(self.lc.builder, llvm_value, "");
+        }
         build_store(self.lc.builder, llvm_value, llvm_value_ref);
 
         Object::Null
@@ -132,16 +188,34 @@ impl Eval {
         index_expr: Expression,
         assign_expr: Expression,
         env: &mut Environment,
+        location: Location,
     ) -> Object {
-        let identify_object = env.get(&ident.0, Location::new(0)); // TODO
-        let llvm_value_ref = match identify_object {
-            Object::Integer(reference) => reference,
-            Object::Boolean(reference) => reference,
-            Object::Array(_, value, _) => value,
-            _ => 0 as *mut LLVMValue,
+        let identify_object = env.get(&ident.0, location.clone());
+        if let Object::Error(_) = identify_object {
+            return identify_object;
+        }
+
+        let (child_type, llvm_value_ref, length) = match identify_object {
+            Object::Array(child_type, value, length) => (child_type, value, length),
+            _ => {
+                return Object::Error(format!(
+                    "{} is not an array. row: {}, column: {}",
+                    ident.0, location.row, location.column
+                ));
+            }
         };
+        if env.is_mutable(&ident.0) == false {
+            return Object::Error(format!(
+                "cannot assign to element of immutable array {}. row: {}, column: {}",
+                ident.0, location.row, location.column
+            ));
+        }
 
         let mut index_object = self.eval_expression(index_expr, &mut env.clone());
+        if let Object::Integer(index_type, index) = index_object {
+            let length = const_int(int32_type(), length as u64);
+            self.build_bounds_check(index_type, index, length, &location);
+        }
         let llvm_index_value = unwrap_object(&mut index_object);
 
         let llvm_element_value_ref = build_gep(
@@ -151,13 +225,38 @@ impl Eval {
             "",
         );
 
-        let mut assign_object = self.eval_expression(assign_expr, &mut env.clone());
+        let assign_object =
+            self.eval_expression_with_hint(assign_expr, &child_type, &mut env.clone());
+        let mut assign_object = check_integer_type(assign_object, &child_type, &location);
+        if let Object::Error(_) = assign_object {
+            return assign_object;
+        }
         let llvm_assign_value = unwrap_object(&mut assign_object);
         build_store(self.lc.builder, llvm_assign_value, llvm_element_value_ref);
 
         Object::Null
     }
 
+    pub fn check_assignable(
+        &self,
+        identify_object: &Object,
+        ident: &Identifier,
+        env: &Environment,
+        location: &Location,
+    ) -> Result<(), Object> {
+        if let Object::Error(_) = identify_object {
+            return Err(identify_object.clone());
+        }
+
+        if env.is_mutable(&ident.0) == false {
+            return Err(Object::Error(format!(
+                "cannot assign twice to immutable variable {}. row: {}, column: {}",
+                ident.0, location.row, location.column
+            )));
+        }
+        Ok(())
+    }
+
     pub fn eval_let_statement(
         &mut self,
         ident: Identifier,
@@ -166,18 +265,24 @@ impl Eval {
         env: &mut Environment,
     ) -> Object {
         let mut object = self.eval_expression(expr, env);
+        if let Object::Error(_) = object {
+            return object;
+        }
         // let llvm_type = convert_llvm_type(expr_type.clone());
         let llvm_value = unwrap_object(&mut object);
 
         match expr_type {
-            LLVMExpressionType::Function
-            | LLVMExpressionType::Array(_, _)
-            | LLVMExpressionType::String(_) => env.set(ident.0, object),
+            LLVMExpressionType::Function => env.set(ident.0.to_string(), object),
+            // every binding owns a copy, so the constant literal is never written
+            LLVMExpressionType::Array(_, _) => {
+                let array_value = build_load(self.lc.builder, llvm_value, "");
+                self.set_value_to_identify(array_value, object, &ident.0, env)
+            }
             LLVMExpressionType::Call => match object {
-                Object::Integer(value) | Object::String(value, _) | Object::Boolean(value) => {
+                Object::Integer(_, value) | Object::String(value) | Object::Boolean(value) => {
                     self.set_value_to_identify(value, object, &ident.0, env)
                 }
-                _ => env.set(ident.0, object),
+                _ => env.set(ident.0.to_string(), object),
             },
             _ => self.set_value_to_identify(llvm_value, object, &ident.0, env),
         }
@@ -209,8 +314,15 @@ impl Eval {
         }
     }
 
-    pub fn eval_return_statement(&mut self, expr: Expression, env: &mut Environment) -> Object {
-        self.eval_expression(expr, env)
+    pub fn eval_return_statement(
+        &mut self,
+        expr: Expression,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let return_type = self.function_stack.last_return_type();
+        let object = self.eval_expression_with_hint(expr, &return_type, env);
+        check_integer_type(object, &return_type, &location)
     }
 
     pub fn eval_while_statement(
@@ -301,6 +413,9 @@ impl Eval {
             Expression::ArrayElement(ident, index_expression, location) => {
                 self.eval_array_element(ident, *index_expression, env, location)
             }
+            Expression::Slice(ident, start, end, location) => {
+                self.eval_slice(ident, start, end, env, location)
+            }
             Expression::Boolean(boolean, _location) => Object::Boolean(llvm_bool!(boolean)),
             Expression::Call(Call {
                 function,
@@ -310,12 +425,14 @@ impl Eval {
             Expression::Function {
                 parameters,
                 parameter_types,
+                parameter_mutabilities,
                 body,
                 return_type,
                 location,
             } => self.eval_function(
                 parameters,
                 parameter_types,
+                parameter_mutabilities,
                 body,
                 return_type,
                 env,
@@ -325,19 +442,94 @@ impl Eval {
             Expression::Infix(infix, left, right, location) => {
                 self.eval_infix(infix, left, right, env, location)
             }
-            Expression::IntegerLiteral(int, _location) => Object::Integer(llvm_integer!(int)),
-            Expression::Prefix(prefix, expr, location) => {
-                self.eval_prefix(prefix, expr, env, location)
+            Expression::Cast(expr, expression_type, location) => {
+                let object = self.eval_expression(*expr, env);
+                calculate_cast(self.lc.builder, object, expression_type, location)
+            }
+            Expression::IntegerLiteral(int, integer_type, location) => self.eval_integer_literal(
+                int,
+                integer_type.unwrap_or(IntegerType::I32),
+                false,
+                location,
+            ),
+            Expression::FloatLiteral(float, _location) => {
+                Object::Float(const_real(double_type(), float))
             }
+            Expression::Prefix(prefix, expr, location) => match *expr {
+                // keep the sign with the literal so that e.g. -128i8 is in range
+                literal @ Expression::IntegerLiteral(_, _, _) => self.eval_expression_with_hint(
+                    Expression::Prefix(prefix, Box::new(literal), location),
+                    &LLVMExpressionType::Integer(IntegerType::I32),
+                    env,
+                ),
+                expr => self.eval_prefix(prefix, Box::new(expr), env, location),
+            },
             Expression::StringLiteral(string, _location) => {
-                // need to include null character(+1)
-                let string_length = (string.len() + 1) as u32;
-                Object::String(codegen_string(&mut self.lc, &string, ""), string_length)
+                Object::String(codegen_string_value(&mut self.lc, &string))
+            }
+            Expression::Interpolation(texts, expressions, location) => {
+                self.eval_interpolation(texts, expressions, env, location)
             }
             _ => Object::Null,
         }
     }
 
+    pub fn eval_interpolation(
+        &mut self,
+        texts: Vec<String>,
+        expressions: Vec<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        match self.eval_interpolation_format(texts, expressions, env, &location) {
+            Ok((format, arguments, max_length)) => Object::String(build_string_format(
+                &mut self.lc,
+                &format,
+                arguments,
+                max_length,
+            )),
+            Err(error) => error,
+        }
+    }
+
+    // "a${x}b" becomes the format "a%db" and the argument x.
+    // also returns an upper bound of the formatted length
+    pub fn eval_interpolation_format(
+        &mut self,
+        texts: Vec<String>,
+        expressions: Vec<Expression>,
+        env: &mut Environment,
+        location: &Location,
+    ) -> Result<(String, Vec<*mut LLVMValue>, *mut LLVMValue), Object> {
+        let text_length: usize = texts.iter().map(|text| text.len()).sum();
+        let mut max_length = const_int(int32_type(), text_length as u64);
+        let mut format = escape_format_text(&texts[0]);
+        let mut arguments = Vec::new();
+
+        for (expression, text) in expressions.into_iter().zip(texts.iter().skip(1)) {
+            let description = expression.string();
+            let object = self.eval_expression(expression, env);
+            if let Object::Error(_) = object {
+                return Err(object);
+            }
+            let (conversion, argument, length) = match format_object(&mut self.lc, object) {
+                Some(formatted) => formatted,
+                None => {
+                    return Err(Object::Error(format!(
+                        "{} cannot be interpolated. row: {}, column: {}",
+                        description, location.row, location.column
+                    )))
+                }
+            };
+
+            format.push_str(conversion);
+            format.push_str(&escape_format_text(text));
+            arguments.push(argument);
+            max_length = add_variable(self.lc.builder, max_length, length, "");
+        }
+        Ok((format, arguments, max_length))
+    }
+
     pub fn eval_array(
         &mut self,
         expression_type: LLVMExpressionType,
@@ -346,13 +538,14 @@ impl Eval {
     ) -> Object {
         let object_vec: Vec<_> = elements
             .into_iter()
-            .map(
-                |element| match self.eval_expression(element, &mut env.clone()) {
-                    Object::Integer(reference) => reference,
+            .map(|element| {
+                match self.eval_expression_with_hint(element, &expression_type, &mut env.clone()) {
+                    Object::Integer(_, reference) => reference,
+                    Object::Float(reference) => reference,
                     Object::Boolean(reference) => reference,
                     _ => 0 as *mut LLVMValue,
-                },
-            )
+                }
+            })
             .collect();
         let array_length = object_vec.len();
         let llvm_type = convert_llvm_type(expression_type.clone());
@@ -370,14 +563,45 @@ impl Eval {
     ) -> Object {
         // need to get llvm_value_reference.
         // so access directly
-        let mut obj = env.get(&ident.0, location);
+        let mut obj = env.get(&ident.0, location.clone());
         let child_expression_type = match obj.clone() {
             Object::Array(child_expression_type, _, _) => child_expression_type,
+            Object::String(llvm_value_ref) => {
+                // strings are indexed by byte
+                let string_value = build_load(self.lc.builder, llvm_value_ref, "");
+                let index_object = self.eval_expression_with_hint(
+                    expr,
+                    &LLVMExpressionType::Integer(IntegerType::I32),
+                    env,
+                );
+                return match index_object {
+                    Object::Integer(IntegerType::I32, index) => {
+                        let length = get_string_length(self.lc.builder, string_value);
+                        self.build_index_check(IntegerType::I32, index, length, &location);
+                        Object::Integer(
+                            IntegerType::U8,
+                            build_string_index(self.lc.builder, string_value, index),
+                        )
+                    }
+                    Object::Error(_) => index_object,
+                    _ => Object::Error(format!(
+                        "string index should be i32, but actually {}. row: {}, column: {}",
+                        index_object, location.row, location.column
+                    )),
+                };
+            }
+            Object::Error(_) => return obj,
             _ => LLVMExpressionType::Null,
         };
         let array_llvm_value = unwrap_object(&mut obj);
 
         let mut index_object = self.eval_expression(expr, env);
+        if let (Object::Integer(index_type, index), Object::Array(_, _, length)) =
+            (index_object.clone(), obj.clone())
+        {
+            let length = const_int(int32_type(), length as u64);
+            self.build_bounds_check(index_type, index, length, &location);
+        }
         let index_llvm_value = unwrap_object(&mut index_object);
 
         let llvm_child_value = build_gep(
@@ -393,6 +617,196 @@ impl Eval {
         )
     }
 
+    pub fn eval_slice(
+        &mut self,
+        ident: Identifier,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let mut string_object = self.eval_identifier(ident.clone(), env, location.clone());
+        let string_value = match string_object {
+            Object::String(_) => unwrap_object(&mut string_object),
+            Object::Error(_) => return string_object,
+            _ => {
+                return Object::Error(format!(
+                    "{} cannot be sliced. row: {}, column: {}",
+                    ident.0, location.row, location.column
+                ));
+            }
+        };
+
+        let mut bounds = Vec::new();
+        for bound in vec![start, end] {
+            let bound_value = match bound {
+                Some(expr) => match self.eval_expression_with_hint(
+                    *expr,
+                    &LLVMExpressionType::Integer(IntegerType::I32),
+                    env,
+                ) {
+                    Object::Integer(IntegerType::I32, value) => Some(value),
+                    Object::Error(message) => return Object::Error(message),
+                    object => {
+                        return Object::Error(format!(
+                            "slice bound should be i32, but actually {}. row: {}, column: {}",
+                            object, location.row, location.column
+                        ));
+                    }
+                },
+                None => None,
+            };
+            bounds.push(bound_value);
+        }
+
+        let start_value = bounds[0].unwrap_or(const_int(int32_type(), 0));
+        let end_value = bounds[1].unwrap_or(get_string_length(self.lc.builder, string_value));
+        // checked even without --checked, because the copy would read past the string
+        let builder = self.lc.builder;
+        let length = get_string_length(builder, string_value);
+        let start_ok = build_int_sge(builder, start_value, const_int(int32_type(), 0), "");
+        let order_ok = build_int_sle(builder, start_value, end_value, "");
+        let end_ok = build_int_sle(builder, end_value, length, "");
+        let is_ok = and_variable(builder, start_ok, order_ok, "");
+        let is_ok = and_variable(builder, is_ok, end_ok, "");
+        let format = format!(
+            "{}: slice %d:%d out of bounds for length %d\n",
+            self.source_position(&location)
+        );
+        let max_length = const_int(int32_type(), (format.len() + 3 * 11) as u64);
+        build_check(
+            &mut self.lc,
+            is_ok,
+            CHECK_FAILURE_STATUS,
+            &format,
+            vec![start_value, end_value, length],
+            max_length,
+        );
+        Object::String(build_string_slice(
+            &mut self.lc,
+            string_value,
+            start_value,
+            end_value,
+        ))
+    }
+
+    // `file:line:column` of a location for runtime messages, escaped for printf
+    pub fn source_position(&self, location: &Location) -> String {
+        escape_format_text(&format!(
+            "{}:{}:{}",
+            self.file_name,
+            location.row + 1,
+            location.column + 1
+        ))
+    }
+
+    pub fn build_bounds_check(
+        &mut self,
+        index_type: IntegerType,
+        index: *mut LLVMValue,
+        length: *mut LLVMValue,
+        location: &Location,
+    ) {
+        if self.checked {
+            self.build_index_check(index_type, index, length, location);
+        }
+    }
+
+    // strings are always checked, so an index never reads past the bytes of a string
+    pub fn build_index_check(
+        &mut self,
+        index_type: IntegerType,
+        index: *mut LLVMValue,
+        length: *mut LLVMValue,
+        location: &Location,
+    ) {
+        // a negative index becomes larger than any length when compared unsigned
+        let index = cast_integer(self.lc.builder, index_type, IntegerType::I64, index);
+        let length = cast_integer(self.lc.builder, IntegerType::I32, IntegerType::I64, length);
+        let is_ok = build_int_ult(self.lc.builder, index, length, "");
+        let format = format!(
+            "{}: index %lld out of bounds for length %lld\n",
+            self.source_position(location)
+        );
+        let max_length = const_int(int32_type(), (format.len() + 2 * 20) as u64);
+        build_check(
+            &mut self.lc,
+            is_ok,
+            CHECK_FAILURE_STATUS,
+            &format,
+            vec![index, length],
+            max_length,
+        );
+    }
+
+    // division by zero, and overflow of +, -, * and of signed division.
+    // returns the result if the operation itself was built
+    pub fn build_arithmetic_check(
+        &mut self,
+        infix: &Infix,
+        integer_type: IntegerType,
+        left: *mut LLVMValue,
+        right: *mut LLVMValue,
+        location: &Location,
+    ) -> Option<*mut LLVMValue> {
+        let builder = self.lc.builder;
+        let signed = integer_type.is_signed();
+        let position = self.source_position(location);
+        let overflow_format = format!("{}: {} overflow in {}\n", position, integer_type, infix);
+        let overflow_length = const_int(int32_type(), overflow_format.len() as u64);
+
+        let (result, overflowed) = match infix {
+            Infix::Plus => build_overflow_add(&mut self.lc, signed, left, right),
+            Infix::Minus => build_overflow_sub(&mut self.lc, signed, left, right),
+            Infix::Multiply => build_overflow_multiply(&mut self.lc, signed, left, right),
+            Infix::Divide | Infix::Rem => {
+                let zero = const_int(type_of(right), 0);
+                let is_ok = build_int_ne(builder, right, zero, "");
+                let format = format!("{}: division by zero\n", position);
+                let max_length = const_int(int32_type(), format.len() as u64);
+                build_check(
+                    &mut self.lc,
+                    is_ok,
+                    CHECK_FAILURE_STATUS,
+                    &format,
+                    vec![],
+                    max_length,
+                );
+
+                // the minimum divided by -1 does not fit either
+                if signed {
+                    let bit_width = integer_type.bit_width() as u64;
+                    let minimum = const_int(type_of(left), 1 << (bit_width - 1));
+                    let is_minimum = build_int_eq(builder, left, minimum, "");
+                    let minus_one = const_int(type_of(right), u64::max_value());
+                    let is_minus_one = build_int_eq(builder, right, minus_one, "");
+                    let overflowed = and_variable(builder, is_minimum, is_minus_one, "");
+                    let is_ok = build_int_eq(builder, overflowed, const_int(int1_type(), 0), "");
+                    build_check(
+                        &mut self.lc,
+                        is_ok,
+                        CHECK_FAILURE_STATUS,
+                        &overflow_format,
+                        vec![],
+                        overflow_length,
+                    );
+                }
+                return None;
+            }
+            _ => return None,
+        };
+        let is_ok = build_int_eq(builder, overflowed, const_int(int1_type(), 0), "");
+        build_check(
+            &mut self.lc,
+            is_ok,
+            CHECK_FAILURE_STATUS,
+            &overflow_format,
+            vec![],
+            overflow_length,
+        );
+        Some(result)
+    }
+
     pub fn eval_call(
         &mut self,
         outer_function: Box<Expression>,
@@ -402,17 +816,18 @@ impl Eval {
     ) -> Object {
         match *outer_function {
             Expression::Identifier(Identifier(ref string), ref _location) => {
-                let mut call_function = outer_env.get(string, location);
-                self.exec_func(call_function, outer_arguments, outer_env)
+                let mut call_function = outer_env.get(string, location.clone());
+                self.exec_func(call_function, outer_arguments, outer_env, location)
             }
             Expression::Call(call) => {
                 self.stack_arg.push(outer_arguments);
                 self.call_func(call.clone(), call.arguments, outer_env, location)
             }
             _ => Object::Error(format!(
-                "cannot call {}. row: {}",
+                "cannot call {}. row: {}, column: {}",
                 outer_function.string(),
-                location.row
+                location.row,
+                location.column
             )),
         }
     }
@@ -426,11 +841,11 @@ impl Eval {
     ) -> Object {
         match *call.function {
             Expression::Identifier(Identifier(ref string), ref _location) => {
-                let mut call_function = outer_env.get(string, location);
+                let mut call_function = outer_env.get(string, location.clone());
                 self.stack_arg.push(call.arguments);
 
                 while let Some(arg) = self.stack_arg.pop() {
-                    call_function = self.exec_func(call_function, arg, outer_env);
+                    call_function = self.exec_func(call_function, arg, outer_env, location.clone());
 
                     match call_function {
                         Object::Function(_) => {
@@ -453,9 +868,10 @@ impl Eval {
                 )
             }
             _ => Object::Error(format!(
-                "cannot call {}. row: {}",
+                "cannot call {}. row: {}, column: {}",
                 call.function.string(),
-                location.row
+                location.row,
+                location.column
             )),
         }
     }
@@ -465,40 +881,186 @@ impl Eval {
         maybe_func_obj: Object,
         outer_arguments: Vec<Expression>,
         outer_env: &mut Environment,
+        location: Location,
     ) -> Object {
         match maybe_func_obj {
             Object::Function(func) => {
-                let function_argments: Vec<*mut LLVMValue> = outer_arguments
-                    .into_iter()
-                    .map(|elem| {
-                        let mut object = self.eval_expression(elem, &mut outer_env.clone());
-                        unwrap_object(&mut object)
-                    })
-                    .collect();
+                let mut function_argments = Vec::new();
+                for (index, elem) in outer_arguments.into_iter().enumerate() {
+                    let parameter_type = match func.parameter_types.get(index) {
+                        Some(parameter_type) => parameter_type.clone(),
+                        None => LLVMExpressionType::Null,
+                    };
+                    let object = self.eval_expression_with_hint(
+                        elem,
+                        &parameter_type,
+                        &mut outer_env.clone(),
+                    );
+                    let mut object = check_integer_type(object, &parameter_type, &location);
+                    if let Object::Error(_) = object {
+                        return object;
+                    }
+                    function_argments.push(unwrap_object(&mut object));
+                }
                 let llvm_value =
                     call_function(self.lc.builder, func.llvm_value, function_argments, "");
                 wrap_llvm_value(func.return_type, llvm_value)
             }
+            Object::Extern(extern_function) => {
+                self.call_extern(extern_function, outer_arguments, outer_env, location)
+            }
             Object::BuildIn(build_in) => match build_in {
                 BuildIn::Printf => {
                     let printf = self.lc.built_ins["printf"];
-                    let function_argments: Vec<*mut LLVMValue> = outer_arguments
+
+                    // an interpolated string is printed directly instead of being built first.
+                    // it is the whole format, because its values are not escaped for printf
+                    if let Some(Expression::Interpolation(texts, expressions, _)) =
+                        outer_arguments.first().cloned()
+                    {
+                        if outer_arguments.len() > 1 {
+                            return Object::Error(format!(
+                                "printf takes no arguments after an interpolated string, but got {}. row: {}, column: {}",
+                                outer_arguments.len() - 1,
+                                location.row,
+                                location.column
+                            ));
+                        }
+                        let format = self.eval_interpolation_format(
+                            texts,
+                            expressions,
+                            &mut outer_env.clone(),
+                            &location,
+                        );
+                        return match format {
+                            Ok((format, mut arguments, _)) => {
+                                let mut function_argments =
+                                    vec![codegen_string(&mut self.lc, &format, ".str")];
+                                function_argments.append(&mut arguments);
+                                call_function(self.lc.builder, printf, function_argments, "");
+                                Object::Null
+                            }
+                            Err(error) => error,
+                        };
+                    }
+                    let mut objects = Vec::new();
+                    for elem in outer_arguments.iter() {
+                        let object = self.eval_expression(elem.clone(), &mut outer_env.clone());
+                        if let Object::Error(_) = object {
+                            return object;
+                        }
+                        objects.push(object);
+                    }
+                    if let Some(Expression::StringLiteral(format, format_location)) =
+                        outer_arguments.first()
+                    {
+                        if let Err(error) = check_format(
+                            format,
+                            format_location,
+                            &objects[1..],
+                            &outer_arguments[1..],
+                            &location,
+                        ) {
+                            return error;
+                        }
+                    }
+
+                    let function_argments: Vec<*mut LLVMValue> = objects
                         .into_iter()
-                        .map(|elem| {
-                            let mut object = self.eval_expression(elem, &mut outer_env.clone());
-                            unwrap_object(&mut object)
+                        .map(|object| match object {
+                            // variadic arguments narrower than int are promoted like C does
+                            Object::Integer(integer_type, llvm_value)
+                                if integer_type.bit_width() < 32 =>
+                            {
+                                cast_integer(
+                                    self.lc.builder,
+                                    integer_type,
+                                    IntegerType::I32,
+                                    llvm_value,
+                                )
+                            }
+                            Object::String(string_value) => {
+                                get_string_pointer(self.lc.builder, string_value)
+                            }
+                            Object::Boolean(llvm_value) => build_cast(
+                                self.lc.builder,
+                                LLVMOpcode::LLVMZExt,
+                                llvm_value,
+                                int32_type(),
+                                "",
+                            ),
+                            mut object => unwrap_object(&mut object),
                         })
                         .collect();
 
                     call_function(self.lc.builder, printf, function_argments, "");
                     Object::Null
                 }
-                BuildIn::Length => {
-                    let array_length = match self
-                        .eval_expression(outer_arguments[0].clone(), &mut outer_env.clone())
-                    {
+                BuildIn::Print => self.eval_print(outer_arguments, false, outer_env, location),
+                BuildIn::Println => self.eval_print(outer_arguments, true, outer_env, location),
+                BuildIn::ReadLine | BuildIn::ReadInt | BuildIn::ReadOk
+                    if !outer_arguments.is_empty() =>
+                {
+                    Object::Error(format!(
+                        "{} takes no arguments, but got {}. row: {}, column: {}",
+                        Object::BuildIn(build_in),
+                        outer_arguments.len(),
+                        location.row,
+                        location.column
+                    ))
+                }
+                BuildIn::ReadLine => {
+                    let stdin = build_stdin(&mut self.lc);
+                    Object::String(build_read_line(&mut self.lc, stdin))
+                }
+                BuildIn::ReadInt => {
+                    let stdin = build_stdin(&mut self.lc);
+                    Object::Integer(IntegerType::I32, build_read_int(&mut self.lc, stdin))
+                }
+                BuildIn::ReadOk => Object::Boolean(build_read_status(&mut self.lc)),
+                BuildIn::Open
+                | BuildIn::ReadAll
+                | BuildIn::Write
+                | BuildIn::Close
+                | BuildIn::Exists => {
+                    self.eval_file_built_in(build_in, outer_arguments, outer_env, location)
+                }
+                BuildIn::Argc | BuildIn::Argv | BuildIn::Getenv | BuildIn::Exit => {
+                    self.eval_process_built_in(build_in, outer_arguments, outer_env, location)
+                }
+                BuildIn::Assert | BuildIn::Panic => {
+                    self.eval_assert(build_in, outer_arguments, outer_env, location)
+                }
+                BuildIn::Abs | BuildIn::Min | BuildIn::Max | BuildIn::Pow | BuildIn::Sqrt => {
+                    self.eval_math_built_in(build_in, outer_arguments, outer_env, location)
+                }
+                BuildIn::Seed | BuildIn::Rand | BuildIn::RandRange | BuildIn::ClockMs => {
+                    self.eval_random_built_in(build_in, outer_arguments, outer_env, location)
+                }
+                BuildIn::Length | BuildIn::Len => {
+                    let argument = match outer_arguments.len() {
+                        1 => {
+                            self.eval_expression(outer_arguments[0].clone(), &mut outer_env.clone())
+                        }
+                        _ => Object::Null,
+                    };
+                    let array_length = match argument {
                         Object::Array(_, _, array_length) => array_length,
-                        _ => panic!("length cannot use for {:?}", outer_arguments[0]),
+                        Object::String(string_value) => {
+                            return Object::Integer(
+                                IntegerType::I32,
+                                get_string_length(self.lc.builder, string_value),
+                            );
+                        }
+                        Object::Error(_) => return argument,
+                        _ => {
+                            return Object::Error(format!(
+                                "{} expects (value: string) or (value: array). row: {}, column: {}",
+                                Object::BuildIn(build_in),
+                                location.row,
+                                location.column
+                            ))
+                        }
                     };
                     let llvm_value_ref = build_alloca(self.lc.builder, int32_type(), "");
                     build_store(
@@ -508,408 +1070,2391 @@ impl Eval {
                     );
                     let llvm_value = build_load(self.lc.builder, llvm_value_ref, "");
 
-                    Object::Integer(llvm_value)
+                    Object::Integer(IntegerType::I32, llvm_value)
+                }
+                BuildIn::ToFloat | BuildIn::ToInt => {
+                    if outer_arguments.len() != 1 {
+                        return Object::Error(format!(
+                            "{} takes 1 argument, but got {}. row: {}, column: {}",
+                            Object::BuildIn(build_in),
+                            outer_arguments.len(),
+                            location.row,
+                            location.column
+                        ));
+                    }
+                    let object =
+                        self.eval_expression(outer_arguments[0].clone(), &mut outer_env.clone());
+                    let cast_type = match build_in {
+                        BuildIn::ToFloat => LLVMExpressionType::Float,
+                        _ => LLVMExpressionType::Integer(IntegerType::I32),
+                    };
+                    calculate_cast(self.lc.builder, object, cast_type, location)
                 }
             },
             _ => maybe_func_obj,
         }
     }
 
-    pub fn eval_function(
+    pub fn eval_print(
         &mut self,
-        parameters: Vec<Identifier>,
-        parameter_types: Vec<LLVMExpressionType>,
-        block: BlockStatement,
-        return_type: LLVMExpressionType,
+        arguments: Vec<Expression>,
+        newline: bool,
         env: &mut Environment,
-        _location: Location,
+        location: Location,
     ) -> Object {
-        let mut converted: Vec<*mut LLVMType> = parameter_types
-            .clone()
-            .into_iter()
-            .map(|elem| convert_llvm_type(elem))
-            .collect();
-
-        let fn_type = function_type(convert_llvm_type(return_type.clone()), &mut converted);
-        let (target_func, func_block) = create_function(&mut self.lc, fn_type);
-        self.function_stack.push(target_func);
-
-        let mut func_env = env.clone();
-        for (index, Identifier(string)) in parameters.clone().into_iter().enumerate() {
-            func_env.set(
-                string,
-                Object::Argument(parameter_types[index].clone(), target_func, index as u32),
-            );
+        let name = if newline { "println" } else { "print" };
+        let argument = match arguments.len() {
+            0 if newline => None,
+            1 => Some(arguments[0].clone()),
+            _ => {
+                return Object::Error(format!(
+                    "{} takes 1 argument, but got {}. row: {}, column: {}",
+                    name,
+                    arguments.len(),
+                    location.row,
+                    location.column
+                ))
+            }
+        };
+        let printf = self.lc.built_ins["printf"];
+        let line_end = if newline { "\n" } else { "" };
+
+        if let Some(argument) = argument {
+            let description = argument.string();
+            let object = self.eval_expression(argument, &mut env.clone());
+            match object {
+                Object::Array(child_type, llvm_value, length) => {
+                    let print_array = get_print_array_function(&mut self.lc, child_type);
+                    let elements = build_gep(
+                        self.lc.builder,
+                        llvm_value,
+                        vec![const_int(int32_type(), 0), const_int(int32_type(), 0)],
+                        "",
+                    );
+                    let length = const_int(int32_type(), length as u64);
+                    call_function(self.lc.builder, print_array, vec![elements, length], "");
+                }
+                Object::Error(_) => return object,
+                object => match format_object(&mut self.lc, object) {
+                    Some((conversion, value, _)) => {
+                        let format = format!("{}{}", conversion, line_end);
+                        let format = codegen_string(&mut self.lc, &format, ".str");
+                        call_function(self.lc.builder, printf, vec![format, value], "");
+                        return Object::Null;
+                    }
+                    None => {
+                        return Object::Error(format!(
+                            "{} cannot be printed. row: {}, column: {}",
+                            description, location.row, location.column
+                        ))
+                    }
+                },
+            }
         }
-
-        self.eval_program(block, &mut func_env);
-
-        if return_type == LLVMExpressionType::Null {
-            build_ret_void(self.lc.builder);
+        if newline {
+            let format = codegen_string(&mut self.lc, line_end, ".str");
+            call_function(self.lc.builder, printf, vec![format], "");
         }
-
-        build_position_at_end(self.lc.builder, self.main_block);
-        let _ = self.function_stack.pop();
-
-        Object::Function(Function {
-            return_type: return_type,
-            llvm_value: target_func,
-            llvm_block: func_block,
-        })
+        Object::Null
     }
 
-    pub fn eval_identifier(
-        &self,
-        ident: Identifier,
+    // files are i64 handles of a table of open files. 0 is a file which failed to open
+    pub fn eval_file_built_in(
+        &mut self,
+        build_in: BuildIn,
+        arguments: Vec<Expression>,
         env: &mut Environment,
         location: Location,
     ) -> Object {
-        let obj = env.get(&ident.0, location);
+        let mut objects = Vec::new();
+        for argument in arguments {
+            let object = self.eval_expression(argument, &mut env.clone());
+            if let Object::Error(_) = object {
+                return object;
+            }
+            objects.push(object);
+        }
 
-        match obj {
-            Object::Integer(llvm_val_ref) => {
-                Object::Integer(build_load(self.lc.builder, llvm_val_ref, ""))
+        match (build_in.clone(), objects.as_slice()) {
+            (BuildIn::Open, [Object::String(path), Object::String(mode)]) => {
+                let path = get_string_pointer(self.lc.builder, *path);
+                let mode = get_string_pointer(self.lc.builder, *mode);
+                Object::Integer(IntegerType::I64, build_open(&mut self.lc, path, mode))
             }
-            Object::Boolean(llvm_val_ref) => {
-                Object::Boolean(build_load(self.lc.builder, llvm_val_ref, ""))
+            (BuildIn::ReadAll, [Object::Integer(IntegerType::I64, file)]) => {
+                Object::String(build_read_all(&mut self.lc, *file))
             }
-            Object::Argument(expression_type, func, index) => {
-                let llvm_value = get_param(func, index);
-                wrap_llvm_value(expression_type, llvm_value)
+            (BuildIn::Write, [Object::Integer(IntegerType::I64, file), Object::String(text)]) => {
+                Object::Integer(IntegerType::I32, build_write(&mut self.lc, *file, *text))
+            }
+            (BuildIn::Close, [Object::Integer(IntegerType::I64, file)]) => {
+                Object::Integer(IntegerType::I32, build_close(&mut self.lc, *file))
+            }
+            (BuildIn::Exists, [Object::String(path)]) => {
+                let path = get_string_pointer(self.lc.builder, *path);
+                Object::Boolean(build_exists(&mut self.lc, path))
+            }
+            _ => {
+                let parameters = match build_in {
+                    BuildIn::Open => "(path: string, mode: string)",
+                    BuildIn::Write => "(file: i64, text: string)",
+                    BuildIn::Exists => "(path: string)",
+                    _ => "(file: i64)",
+                };
+                Object::Error(format!(
+                    "{} expects {}. row: {}, column: {}",
+                    Object::BuildIn(build_in),
+                    parameters,
+                    location.row,
+                    location.column
+                ))
             }
-            _ => obj,
         }
     }
 
-    pub fn eval_infix(
+    pub fn eval_process_built_in(
         &mut self,
-        infix: Infix,
-        left: Box<Expression>,
-        right: Box<Expression>,
+        build_in: BuildIn,
+        arguments: Vec<Expression>,
         env: &mut Environment,
         location: Location,
     ) -> Object {
-        let left_object = self.eval_expression(*left, env);
-        let right_object = self.eval_expression(*right, env);
+        let mut objects = Vec::new();
+        for argument in arguments {
+            let object = self.eval_expression_with_hint(
+                argument,
+                &LLVMExpressionType::Integer(IntegerType::I32),
+                &mut env.clone(),
+            );
+            if let Object::Error(_) = object {
+                return object;
+            }
+            objects.push(object);
+        }
 
-        match left_object {
-            Object::Integer(left) => {
-                resolve_left_integer(self.lc.builder, infix, left, right_object, location)
+        match (build_in.clone(), objects.as_slice()) {
+            (BuildIn::Argc, []) => Object::Integer(IntegerType::I32, build_argc(&mut self.lc)),
+            (BuildIn::Argv, [Object::Integer(IntegerType::I32, index)]) => {
+                Object::String(build_argv(&mut self.lc, *index))
             }
-            Object::Boolean(left) => {
-                resolve_left_boolean(self.lc.builder, infix, left, right_object, location)
+            (BuildIn::Getenv, [Object::String(name)]) => {
+                let name = get_string_pointer(self.lc.builder, *name);
+                Object::String(build_getenv(&mut self.lc, name))
             }
-            Object::Argument(expression_type_left, func, index) => {
-                let left = get_param(func, index);
-                resolve_left_argument(
-                    self.lc.builder,
-                    infix,
-                    expression_type_left,
-                    left,
-                    right_object,
-                    location,
-                )
+            (BuildIn::Exit, [Object::Integer(IntegerType::I32, code)]) => {
+                build_exit(&mut self.lc, *code);
+                Object::Null
+            }
+            _ => {
+                let parameters = match build_in {
+                    BuildIn::Argc => "()",
+                    BuildIn::Argv => "(index: int)",
+                    BuildIn::Getenv => "(name: string)",
+                    _ => "(code: int)",
+                };
+                Object::Error(format!(
+                    "{} expects {}. row: {}, column: {}",
+                    Object::BuildIn(build_in),
+                    parameters,
+                    location.row,
+                    location.column
+                ))
             }
-            Object::String(left, _) => resolve_left_string(infix, left, right_object, location),
-            _ => resolve_left_failed(infix, left_object, right_object, location),
         }
     }
 
-    pub fn eval_prefix(
+    // abs, min, max, pow and sqrt on integers and floats
+    pub fn eval_math_built_in(
         &mut self,
-        prefix: Prefix,
-        expr: Box<Expression>,
+        build_in: BuildIn,
+        arguments: Vec<Expression>,
         env: &mut Environment,
         location: Location,
     ) -> Object {
-        let expr_value = self.eval_expression(*expr, env);
-        match expr_value {
-            Object::Integer(value) => calculate_prefix_integer(self.lc.builder, prefix, value),
-            Object::Boolean(value) => calculate_prefix_boolean(prefix, value, location),
-            _ => Object::Error(format!(
-                "expr value should be integer, but actually {}. row: {}",
-                expr_value, location.row,
-            )),
+        let objects = if arguments.len() == 2 {
+            let (left, right) =
+                self.eval_operands(arguments[0].clone(), arguments[1].clone(), &mut env.clone());
+            vec![left, right]
+        } else {
+            arguments
+                .into_iter()
+                .map(|argument| self.eval_expression(argument, &mut env.clone()))
+                .collect()
+        };
+        for object in &objects {
+            if let Object::Error(_) = object {
+                return object.clone();
+            }
         }
-    }
 
-    pub fn accumultae_error(&mut self, obj: Object) -> Option<Object> {
-        match obj {
-            Object::Error(_) => {
-                self.error_stack.push(obj);
-                None
+        let builder = self.lc.builder;
+        let result = match (build_in.clone(), objects.as_slice()) {
+            (BuildIn::Abs, [Object::Integer(integer_type, value)]) => {
+                let value = if integer_type.is_signed() {
+                    build_int_abs(builder, *value)
+                } else {
+                    *value
+                };
+                Some(Object::Integer(*integer_type, value))
             }
-            _ => Some(obj),
-        }
-    }
+            (BuildIn::Abs, [Object::Float(value)]) => {
+                Some(Object::Float(build_float_abs(&mut self.lc, *value)))
+            }
+            (
+                BuildIn::Min,
+                [Object::Integer(left_type, left), Object::Integer(right_type, right)],
+            ) if left_type == right_type => {
+                let signed = left_type.is_signed();
+                let result = build_int_min(builder, signed, *left, *right);
+                Some(Object::Integer(*left_type, result))
+            }
+            (BuildIn::Min, [Object::Float(left), Object::Float(right)]) => {
+                Some(Object::Float(build_float_min(&mut self.lc, *left, *right)))
+            }
+            (
+                BuildIn::Max,
+                [Object::Integer(left_type, left), Object::Integer(right_type, right)],
+            ) if left_type == right_type => {
+                let signed = left_type.is_signed();
+                let result = build_int_max(builder, signed, *left, *right);
+                Some(Object::Integer(*left_type, result))
+            }
+            (BuildIn::Max, [Object::Float(left), Object::Float(right)]) => {
+                Some(Object::Float(build_float_max(&mut self.lc, *left, *right)))
+            }
+            (BuildIn::Pow, [base, exponent]) => self.build_power(base, exponent),
+            (BuildIn::Sqrt, [Object::Integer(integer_type, value)]) => {
+                let signed = integer_type.is_signed();
+                let result = build_int_sqrt(&mut self.lc, signed, *value);
+                Some(Object::Integer(*integer_type, result))
+            }
+            (BuildIn::Sqrt, [Object::Float(value)]) => {
+                Some(Object::Float(build_float_sqrt(&mut self.lc, *value)))
+            }
+            _ => None,
+        };
 
-    pub fn dump_llvm(&mut self) {
-        self.lc.dump();
-        validate_module(self.lc.module);
+        result.unwrap_or_else(|| {
+            let parameters = match build_in {
+                BuildIn::Abs | BuildIn::Sqrt => "(value: int) or (value: float)",
+                BuildIn::Pow => "(base: int, exponent: int) or (base: float, exponent: float)",
+                _ => "(left: int, right: int) or (left: float, right: float)",
+            };
+            Object::Error(format!(
+                "{} expects {}. row: {}, column: {}",
+                Object::BuildIn(build_in),
+                parameters,
+                location.row,
+                location.column
+            ))
+        })
     }
 
-    pub fn emit_error(&mut self) -> String {
-        let mut error_message = String::new();
-        for (index, err_obj) in self.error_stack.iter().enumerate() {
-            if index == 0 {
-                error_message = format!("{}", err_obj);
-            } else {
-                error_message = format!("{}\n{}", error_message, err_obj);
+    pub fn eval_random_built_in(
+        &mut self,
+        build_in: BuildIn,
+        arguments: Vec<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let mut objects = Vec::new();
+        for argument in arguments {
+            let object = self.eval_expression_with_hint(
+                argument,
+                &LLVMExpressionType::Integer(IntegerType::I32),
+                &mut env.clone(),
+            );
+            if let Object::Error(_) = object {
+                return object;
+            }
+            objects.push(object);
+        }
+
+        match (build_in.clone(), objects.as_slice()) {
+            (BuildIn::Seed, [Object::Integer(IntegerType::I32, seed)]) => {
+                build_seed(&mut self.lc, *seed);
+                Object::Null
+            }
+            (BuildIn::Rand, []) => Object::Integer(IntegerType::I32, build_rand(&mut self.lc)),
+            (
+                BuildIn::RandRange,
+                [Object::Integer(IntegerType::I32, low), Object::Integer(IntegerType::I32, high)],
+            ) => Object::Integer(
+                IntegerType::I32,
+                build_rand_range(&mut self.lc, *low, *high),
+            ),
+            (BuildIn::ClockMs, []) => {
+                Object::Integer(IntegerType::I64, build_clock_ms(&mut self.lc))
+            }
+            _ => {
+                let parameters = match build_in {
+                    BuildIn::Seed => "(seed: int)",
+                    BuildIn::RandRange => "(low: int, high: int)",
+                    _ => "()",
+                };
+                Object::Error(format!(
+                    "{} expects {}. row: {}, column: {}",
+                    Object::BuildIn(build_in),
+                    parameters,
+                    location.row,
+                    location.column
+                ))
             }
         }
-        error_message.to_string()
     }
 
-    pub fn emit_llvm(&mut self, file_name: &str) {
-        self.lc.emit_file(file_name);
+    // assert(condition), assert(condition, message) and panic(message)
+    pub fn eval_assert(
+        &mut self,
+        build_in: BuildIn,
+        arguments: Vec<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let parameters = match build_in {
+            BuildIn::Assert => "(condition: boolean) or (condition: boolean, message: string)",
+            _ => "(message: string)",
+        };
+        let arguments_error = Object::Error(format!(
+            "{} expects {}. row: {}, column: {}",
+            Object::BuildIn(build_in.clone()),
+            parameters,
+            location.row,
+            location.column
+        ));
+        let mut arguments = arguments.into_iter();
+        let (condition, message) = match (build_in.clone(), arguments.len()) {
+            (BuildIn::Assert, 1) | (BuildIn::Assert, 2) => (arguments.next(), arguments.next()),
+            (BuildIn::Panic, 1) => (None, arguments.next()),
+            _ => return arguments_error,
+        };
+
+        let mut format = match condition {
+            Some(ref condition) => format!(
+                "{}: assertion failed: {}",
+                self.source_position(&location),
+                escape_format_text(&condition.string())
+            ),
+            None => format!("{}: panic", self.source_position(&location)),
+        };
+
+        // the message is only evaluated once the assertion has failed
+        let ok_block = match condition {
+            Some(condition) => match self.eval_expression(condition, &mut env.clone()) {
+                Object::Boolean(condition) => {
+                    let function = get_block_function(get_insert_block(self.lc.builder));
+                    let failure_block =
+                        append_basic_block_in_context(self.lc.context, function, "");
+                    let ok_block = append_basic_block_in_context(self.lc.context, function, "");
+                    build_cond_br(self.lc.builder, condition, ok_block, failure_block);
+                    build_position_at_end(self.lc.builder, failure_block);
+                    Some(ok_block)
+                }
+                object @ Object::Error(_) => return object,
+                _ => return arguments_error,
+            },
+            None => None,
+        };
+
+        let mut arguments = Vec::new();
+        let mut max_length = const_int(int32_type(), format.len() as u64 + 3);
+        if let Some(message) = message {
+            let message = match self.eval_expression(message, &mut env.clone()) {
+                Object::String(message) => message,
+                object @ Object::Error(_) => return object,
+                _ => return arguments_error,
+            };
+            format.push_str(": %s");
+            arguments.push(get_string_pointer(self.lc.builder, message));
+            let length = get_string_length(self.lc.builder, message);
+            max_length = add_variable(self.lc.builder, max_length, length, "");
+        }
+        format.push_str("\n");
+
+        let message = build_string_format(&mut self.lc, &format, arguments, max_length);
+        let status = const_int(int32_type(), PANIC_STATUS);
+        build_abort(&mut self.lc, message, status);
+        if let Some(ok_block) = ok_block {
+            build_unreachable(self.lc.builder);
+            build_position_at_end(self.lc.builder, ok_block);
+        }
+        Object::Null
     }
 
-    pub fn has_error(&self) -> bool {
-        self.error_stack.len() > 0
+    pub fn eval_function(
+        &mut self,
+        parameters: Vec<Identifier>,
+        parameter_types: Vec<LLVMExpressionType>,
+        parameter_mutabilities: Vec<Mutability>,
+        block: BlockStatement,
+        return_type: LLVMExpressionType,
+        env: &mut Environment,
+        _location: Location,
+    ) -> Object {
+        let mut converted: Vec<*mut LLVMType> = parameter_types
+            .clone()
+            .into_iter()
+            .map(|elem| convert_llvm_type(elem))
+            .collect();
+
+        let fn_type = function_type(convert_llvm_type(return_type.clone()), &mut converted);
+        let outer_block = get_insert_block(self.lc.builder);
+        let (target_func, func_block) = create_function(&mut self.lc, fn_type);
+        self.function_stack.push(target_func, return_type.clone());
+
+        // parameters are spilled to allocas so that mut parameters can be reassigned
+        let mut func_env = env.clone();
+        for (index, Identifier(string)) in parameters.into_iter().enumerate() {
+            let parameter_type = parameter_types[index].clone();
+            let llvm_value = get_param(target_func, index as u32);
+            match wrap_llvm_value(parameter_type.clone(), llvm_value) {
+                object @ Object::Integer(_, _)
+                | object @ Object::Float(_)
+                | object @ Object::Boolean(_)
+                | object @ Object::String(_)
+                | object @ Object::Array(_, _, _) => {
+                    self.set_value_to_identify(llvm_value, object, &string, &mut func_env);
+                }
+                _ => {
+                    func_env.set(
+                        string.to_string(),
+                        Object::Argument(parameter_type, target_func, index as u32),
+                    );
+                }
+            };
+            func_env.set_mutability(string.to_string(), parameter_mutabilities[index].clone());
+        }
+
+        self.eval_program(block, &mut func_env);
+
+        if return_type == LLVMExpressionType::Null {
+            build_ret_void(self.lc.builder);
+        }
+
+        build_position_at_end(self.lc.builder, outer_block);
+        let _ = self.function_stack.pop();
+
+        Object::Function(Function {
+            parameter_types: parameter_types,
+            return_type: return_type,
+            llvm_value: target_func,
+            llvm_block: func_block,
+        })
     }
 
-    pub fn setup_main(lc: &mut LLVMCreator) -> (*mut LLVMBasicBlock, *mut LLVMValue) {
-        let fn_type = function_type(int32_type(), &mut []);
-        let main_function = add_function(lc.module, fn_type, "main");
-        let block = append_basic_block_in_context(lc.context, main_function, "entry");
-        build_position_at_end(lc.builder, block);
-        (block, main_function)
+    // defines the function like `let`, under a symbol of its own name
+    pub fn eval_export_statement(
+        &mut self,
+        ident: Identifier,
+        expr: Expression,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let name = ident.0.to_string();
+        let (parameters, parameter_types, return_type) = match expr {
+            Expression::Function {
+                ref parameters,
+                ref parameter_types,
+                ref return_type,
+                ..
+            } => (
+                parameters.clone(),
+                parameter_types.clone(),
+                return_type.clone(),
+            ),
+            _ => unreachable!("export is parsed with a function literal"),
+        };
+        for parameter_type in &parameter_types {
+            if c_type_name(parameter_type).is_none() || *parameter_type == LLVMExpressionType::Null
+            {
+                return Object::Error(format!(
+                    "export fn {} cannot take {}. row: {}, column: {}",
+                    name, parameter_type, location.row, location.column
+                ));
+            }
+        }
+        if c_type_name(&return_type).is_none() {
+            return Object::Error(format!(
+                "export fn {} cannot return {}. row: {}, column: {}",
+                name, return_type, location.row, location.column
+            ));
+        }
+        if get_named_function(self.lc.module, &name).is_null() == false
+            || get_named_global(self.lc.module, &name).is_null() == false
+        {
+            return Object::Error(format!(
+                "export fn {} conflicts with an existing symbol. row: {}, column: {}",
+                name, location.row, location.column
+            ));
+        }
+
+        let object = self.eval_expression(expr, env);
+        if let Object::Function(ref function) = object {
+            export_function(function.llvm_value, &name);
+        } else {
+            return object;
+        }
+        env.set_mutability(name.clone(), Mutability::Immutable);
+        env.set(name.clone(), object);
+
+        self.exports.push(ExportedFunction {
+            name: name,
+            parameters: parameters
+                .iter()
+                .map(|parameter| parameter.0.to_string())
+                .collect(),
+            parameter_types: parameter_types,
+            return_type: return_type,
+        });
+        Object::Null
     }
 
-    pub fn set_value_to_identify(
+    // declares the C function, or reuses a declaration of the same type such as printf
+    pub fn eval_extern_statement(
         &mut self,
-        llvm_value: *mut LLVMValue,
-        mut object: Object,
-        name: &str,
+        extern_function: ExternFunction,
         env: &mut Environment,
     ) -> Object {
-        let llvm_type = get_llvm_type_from_object(&mut object);
-        let llvm_value_ref = build_alloca(self.lc.builder, llvm_type, name);
-        build_store(self.lc.builder, llvm_value, llvm_value_ref);
-        let rewraped_object = rewrap_llvm_value_ref(object, llvm_value_ref);
-        env.set(name.to_string(), rewraped_object)
+        let name = extern_function.name.0.to_string();
+        let row = extern_function.location.row;
+        let column = extern_function.location.column;
+        let mut parameter_types = Vec::new();
+        for parameter_type in &extern_function.parameter_types {
+            match convert_c_type(parameter_type.clone()) {
+                Some(llvm_type) if *parameter_type != LLVMExpressionType::Null => {
+                    parameter_types.push(llvm_type)
+                }
+                _ => {
+                    return Object::Error(format!(
+                        "extern fn {} cannot take {}. row: {}, column: {}",
+                        name, parameter_type, row, column
+                    ));
+                }
+            }
+        }
+        let return_type = match convert_c_type(extern_function.return_type.clone()) {
+            Some(llvm_type) => llvm_type,
+            None => {
+                return Object::Error(format!(
+                    "extern fn {} cannot return {}. row: {}, column: {}",
+                    name, extern_function.return_type, row, column
+                ));
+            }
+        };
+        let fn_type = if extern_function.variadic {
+            function_type_var_arg(return_type, &mut parameter_types)
+        } else {
+            function_type(return_type, &mut parameter_types)
+        };
+
+        // only the declarations of setup_builtin may be shared. any other symbol is the
+        // program's own, or is one which a second declaration would rename
+        let mut function = get_named_function(self.lc.module, &name);
+        if self.lc.built_ins.get(name.as_str()) == Some(&function) {
+            if get_function_type(function) != fn_type {
+                return Object::Error(format!(
+                    "{} conflicts with an existing declaration of {}. row: {}, column: {}",
+                    extern_function.string(),
+                    name,
+                    row,
+                    column
+                ));
+            }
+        } else if function.is_null() && get_named_global(self.lc.module, &name).is_null() {
+            function = add_function(self.lc.module, fn_type, &name);
+        } else {
+            return Object::Error(format!(
+                "extern fn {} conflicts with an existing symbol. row: {}, column: {}",
+                name, row, column
+            ));
+        }
+
+        env.set(
+            name.clone(),
+            Object::Extern(Extern {
+                name: name,
+                llvm_value: function,
+                parameter_types: extern_function.parameter_types,
+                variadic: extern_function.variadic,
+                return_type: extern_function.return_type,
+            }),
+        );
+        Object::Null
     }
-}
 
-#[allow(dead_code)]
-pub fn execute_eval_test(input: &str, expect: u64) {
-    let mut lexer = Lexer::new(&input);
+    // arguments after the fixed parameters of a variadic function get the C default promotions
+    pub fn call_extern(
+        &mut self,
+        extern_function: Extern,
+        arguments: Vec<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let fixed_count = extern_function.parameter_types.len();
+        if arguments.len() < fixed_count
+            || (arguments.len() > fixed_count && extern_function.variadic == false)
+        {
+            let expected = if extern_function.variadic {
+                format!("at least {}", fixed_count)
+            } else {
+                fixed_count.to_string()
+            };
+            let noun = if fixed_count == 1 {
+                "argument"
+            } else {
+                "arguments"
+            };
+            return Object::Error(format!(
+                "{} takes {} {}, but got {}. row: {}, column: {}",
+                extern_function.name,
+                expected,
+                noun,
+                arguments.len(),
+                location.row,
+                location.column
+            ));
+        }
 
-    let mut parser = Parser::new(&mut lexer);
-    let program = parser.parse_program();
-    if parser.has_error() {
-        panic!("{}", parser.emit_error());
+        let builder = self.lc.builder;
+        let mut llvm_arguments = Vec::new();
+        for (index, argument) in arguments.into_iter().enumerate() {
+            let parameter_type = extern_function.parameter_types.get(index);
+            let hint = parameter_type.cloned().unwrap_or(LLVMExpressionType::Null);
+            let object = self.eval_expression_with_hint(argument, &hint, &mut env.clone());
+            let object = check_integer_type(object, &hint, &location);
+            if let Object::Error(_) = object {
+                return object;
+            }
+            if let Some(parameter_type) = parameter_type {
+                let actual_type = get_expression_type_from_object(&object);
+                if actual_type != *parameter_type {
+                    return Object::Error(format!(
+                        "mismatched types: expected {}, but actually {}. row: {}, column: {}",
+                        parameter_type, actual_type, location.row, location.column
+                    ));
+                }
+            }
+
+            let promoted = parameter_type.is_none();
+            let llvm_argument = match object {
+                Object::Integer(integer_type, value)
+                    if promoted && integer_type.bit_width() < 32 =>
+                {
+                    let extend = if integer_type.is_signed() {
+                        LLVMOpcode::LLVMSExt
+                    } else {
+                        LLVMOpcode::LLVMZExt
+                    };
+                    build_cast(builder, extend, value, int32_type(), "")
+                }
+                Object::Boolean(value) if promoted => {
+                    build_cast(builder, LLVMOpcode::LLVMZExt, value, int32_type(), "")
+                }
+                Object::Integer(_, value) | Object::Float(value) | Object::Boolean(value) => value,
+                Object::String(value) => get_string_pointer(builder, value),
+                _ => {
+                    return Object::Error(format!(
+                        "{} cannot be passed to {}. row: {}, column: {}",
+                        object, extern_function.name, location.row, location.column
+                    ));
+                }
+            };
+            llvm_arguments.push(llvm_argument);
+        }
+
+        let result = call_function(builder, extern_function.llvm_value, llvm_arguments, "");
+        match extern_function.return_type {
+            LLVMExpressionType::Null => Object::Null,
+            LLVMExpressionType::String => {
+                Object::String(build_c_string_value(&mut self.lc, result))
+            }
+            return_type => wrap_llvm_value(return_type, result),
+        }
     }
 
-    let mut eval = Eval::new();
+    pub fn eval_identifier(
+        &self,
+        ident: Identifier,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let obj = env.get(&ident.0, location);
 
-    eval.entry_eval_program(program, &mut Environment::new());
-    if eval.has_error() {
-        panic!("{}", eval.emit_error());
+        match obj {
+            Object::Integer(integer_type, llvm_val_ref) => {
+                Object::Integer(integer_type, build_load(self.lc.builder, llvm_val_ref, ""))
+            }
+            Object::Float(llvm_val_ref) => {
+                Object::Float(build_load(self.lc.builder, llvm_val_ref, ""))
+            }
+            Object::Boolean(llvm_val_ref) => {
+                Object::Boolean(build_load(self.lc.builder, llvm_val_ref, ""))
+            }
+            Object::String(llvm_val_ref) => {
+                Object::String(build_load(self.lc.builder, llvm_val_ref, ""))
+            }
+            Object::Argument(expression_type, func, index) => {
+                let llvm_value = get_param(func, index);
+                wrap_llvm_value(expression_type, llvm_value)
+            }
+            _ => obj,
+        }
+    }
+
+    // an untyped integer literal takes the type of the other operand
+    pub fn eval_operands(
+        &mut self,
+        left: Expression,
+        right: Expression,
+        env: &mut Environment,
+    ) -> (Object, Object) {
+        if is_untyped_integer_literal(&left) {
+            let right_object = self.eval_expression(right, env);
+            let hint = get_expression_type_from_object(&right_object);
+            (
+                self.eval_expression_with_hint(left, &hint, env),
+                right_object,
+            )
+        } else {
+            let left_object = self.eval_expression(left, env);
+            let hint = get_expression_type_from_object(&left_object);
+            (
+                left_object,
+                self.eval_expression_with_hint(right, &hint, env),
+            )
+        }
+    }
+
+    // `base ** exponent` on two integers of the same type or two floats
+    pub fn build_power(&mut self, base: &Object, exponent: &Object) -> Option<Object> {
+        match (base, exponent) {
+            (Object::Integer(base_type, base), Object::Integer(exponent_type, exponent))
+                if base_type == exponent_type =>
+            {
+                let type_name = base_type.to_string();
+                let signed = base_type.is_signed();
+                let result = build_int_power(&mut self.lc, &type_name, signed, *base, *exponent);
+                Some(Object::Integer(*base_type, result))
+            }
+            (Object::Float(base), Object::Float(exponent)) => Some(Object::Float(
+                build_float_power(&mut self.lc, *base, *exponent),
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn eval_infix(
+        &mut self,
+        infix: Infix,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let (left_object, right_object) = self.eval_operands(*left, *right, env);
+        if let Object::Error(_) = left_object {
+            return left_object;
+        }
+        if let Object::Error(_) = right_object {
+            return right_object;
+        }
+
+        if let (true, Object::Integer(left_type, left), Object::Integer(right_type, right)) =
+            (self.checked, left_object.clone(), right_object.clone())
+        {
+            if left_type == right_type {
+                if let Some(result) =
+                    self.build_arithmetic_check(&infix, left_type, left, right, &location)
+                {
+                    return Object::Integer(left_type, result);
+                }
+            }
+        }
+
+        if let Infix::Power = infix {
+            return match self.build_power(&left_object, &right_object) {
+                Some(result) => result,
+                None => Object::Error(format!(
+                    "{} expects two integers of the same type or two floats, but got {} and {}. row: {}, column: {}",
+                    infix, left_object, right_object, location.row, location.column
+                )),
+            };
+        }
+
+        match left_object {
+            Object::Integer(integer_type, left) => resolve_left_integer(
+                self.lc.builder,
+                infix,
+                integer_type,
+                left,
+                right_object,
+                location,
+            ),
+            Object::Float(left) => {
+                resolve_left_float(self.lc.builder, infix, left, right_object, location)
+            }
+            Object::Boolean(left) => {
+                resolve_left_boolean(self.lc.builder, infix, left, right_object, location)
+            }
+            Object::Argument(expression_type_left, func, index) => {
+                let left = get_param(func, index);
+                resolve_left_argument(
+                    self.lc.builder,
+                    infix,
+                    expression_type_left,
+                    left,
+                    right_object,
+                    location,
+                )
+            }
+            Object::String(left) => {
+                resolve_left_string(&mut self.lc, infix, left, right_object, location)
+            }
+            _ => resolve_left_failed(infix, left_object, right_object, location),
+        }
+    }
+
+    pub fn eval_prefix(
+        &mut self,
+        prefix: Prefix,
+        expr: Box<Expression>,
+        env: &mut Environment,
+        location: Location,
+    ) -> Object {
+        let expr_value = self.eval_expression(*expr, env);
+        match expr_value {
+            Object::Integer(integer_type, value) => {
+                calculate_prefix_integer(self.lc.builder, prefix, integer_type, value)
+            }
+            Object::Float(value) => {
+                calculate_prefix_float(self.lc.builder, prefix, value, location)
+            }
+            Object::Boolean(value) => calculate_prefix_boolean(prefix, value, location),
+            _ => Object::Error(format!(
+                "expr value should be integer, but actually {}. row: {}, column: {}",
+                expr_value, location.row, location.column,
+            )),
+        }
     }
-    let actual = execute_test_ir_function(eval.lc.module, eval.function_stack.pop());
 
-    assert!(
-        actual == expect,
-        "test failed. \nexpected: {} \nactual: {}",
-        expect,
-        actual
+    // untyped integer literals take their type from the context they are used in
+    pub fn eval_expression_with_hint(
+        &mut self,
+        expr: Expression,
+        hint: &LLVMExpressionType,
+        env: &mut Environment,
+    ) -> Object {
+        let integer_type = match hint {
+            LLVMExpressionType::Integer(integer_type) => *integer_type,
+            _ => return self.eval_expression(expr, env),
+        };
+
+        match expr {
+            Expression::IntegerLiteral(value, None, location) => {
+                self.eval_integer_literal(value, integer_type, false, location)
+            }
+            Expression::Prefix(prefix, expr, location) => match *expr {
+                Expression::IntegerLiteral(value, literal_type, literal_location) => {
+                    let integer_type = literal_type.unwrap_or(integer_type);
+                    let negative = prefix == Prefix::Minus;
+                    match self.eval_integer_literal(value, integer_type, negative, literal_location)
+                    {
+                        Object::Integer(integer_type, llvm_value) => calculate_prefix_integer(
+                            self.lc.builder,
+                            prefix,
+                            integer_type,
+                            llvm_value,
+                        ),
+                        object => object,
+                    }
+                }
+                expr => self.eval_prefix(prefix, Box::new(expr), env, location),
+            },
+            _ => self.eval_expression(expr, env),
+        }
+    }
+
+    pub fn eval_integer_literal(
+        &self,
+        value: u64,
+        integer_type: IntegerType,
+        negative: bool,
+        location: Location,
+    ) -> Object {
+        if integer_type.fits(value, negative) == false {
+            return Object::Error(format!(
+                "integer literal {}{} does not fit in {}. row: {}, column: {}",
+                if negative { "-" } else { "" },
+                value,
+                integer_type,
+                location.row,
+                location.column
+            ));
+        }
+        Object::Integer(
+            integer_type,
+            const_int(convert_integer_type(integer_type), value),
+        )
+    }
+
+    pub fn accumultae_error(&mut self, obj: Object) -> Option<Object> {
+        match obj {
+            Object::Error(_) => {
+                self.error_stack.push(obj);
+                None
+            }
+            _ => Some(obj),
+        }
+    }
+
+    pub fn dump_llvm(&mut self) {
+        self.lc.dump();
+        validate_module(self.lc.module);
+    }
+
+    pub fn emit_error(&mut self) -> String {
+        let mut error_message = String::new();
+        for (index, err_obj) in self.error_stack.iter().enumerate() {
+            if index == 0 {
+                error_message = format!("{}", err_obj);
+            } else {
+                error_message = format!("{}\n{}", error_message, err_obj);
+            }
+        }
+        error_message.to_string()
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error_stack.len() > 0
+    }
+
+    pub fn setup_main(lc: &mut LLVMCreator) -> (*mut LLVMBasicBlock, *mut LLVMValue) {
+        let fn_type = function_type(int32_type(), &mut [int32_type(), argv_type()]);
+        let main_function = add_function(lc.module, fn_type, "main");
+        let block = append_basic_block_in_context(lc.context, main_function, "entry");
+        build_position_at_end(lc.builder, block);
+        build_store_arguments(lc, main_function);
+        (block, main_function)
+    }
+
+    pub fn set_value_to_identify(
+        &mut self,
+        llvm_value: *mut LLVMValue,
+        mut object: Object,
+        name: &str,
+        env: &mut Environment,
+    ) -> Object {
+        let llvm_type = get_llvm_type_from_object(&mut object);
+        let llvm_value_ref = build_alloca(self.lc.builder, llvm_type, name);
+        build_store(self.lc.builder, llvm_value, llvm_value_ref);
+        let rewraped_object = rewrap_llvm_value_ref(object, llvm_value_ref);
+        env.set(name.to_string(), rewraped_object)
+    }
+}
+
+pub fn is_untyped_integer_literal(expr: &Expression) -> bool {
+    match expr {
+        Expression::IntegerLiteral(_, None, _) => true,
+        Expression::Prefix(_, expr, _) => is_untyped_integer_literal(expr),
+        _ => false,
+    }
+}
+
+#[allow(dead_code)]
+pub fn execute_eval_error_test(input: &str, expect: &str) {
+    let mut lexer = Lexer::new(&input);
+
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+    if parser.has_error() {
+        panic!("{}", parser.emit_error());
+    }
+
+    let mut eval = Eval::new();
+
+    eval.entry_eval_program(program, &mut Environment::new());
+    if eval.has_error() == false {
+        panic!("no errors found.");
+    }
+
+    assert!(
+        eval.emit_error() == expect,
+        "\r\nexpected: {:?} \r\nactual: {:?}",
+        expect,
+        eval.emit_error()
+    );
+}
+
+// the program has to give the same result at every optimization level
+#[allow(dead_code)]
+pub fn execute_eval_test(input: &str, expect: u64) {
+    for level in OptimizationLevel::all().iter() {
+        let mut lexer = Lexer::new(&input);
+
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program();
+        if parser.has_error() {
+            panic!("{}", parser.emit_error());
+        }
+
+        let mut eval = Eval::new();
+
+        eval.entry_eval_program(program, &mut Environment::new());
+        if eval.has_error() {
+            panic!("{}", eval.emit_error());
+        }
+        optimize_module(eval.lc.module, *level);
+        let actual = execute_test_ir_function(eval.lc.module, eval.function_stack.pop());
+
+        assert!(
+            actual == expect,
+            "test failed at {:?}. \nexpected: {} \nactual: {}",
+            level,
+            expect,
+            actual
+        );
+    }
+}
+
+// links the program into an executable at every optimization level, and checks its exit status
+// and what it printed
+#[allow(dead_code)]
+pub fn execute_output_test(input: &str, expect_status: i32, expect_stdout: &str) {
+    for level in OptimizationLevel::all().iter() {
+        let mut lexer = Lexer::new(&input);
+
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program();
+        if parser.has_error() {
+            panic!("{}", parser.emit_error());
+        }
+
+        let mut eval = Eval::new();
+        eval.entry_eval_program(program, &mut Environment::new());
+        if eval.has_error() {
+            panic!("{}", eval.emit_error());
+        }
+        let output = run_test_executable(&eval, *level, &TEST_ARGUMENTS[1..]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(
+            output.status.code() == Some(expect_status) && stdout == expect_stdout,
+            "test failed at {:?}. \nexpected: {} {:?} \nactual: {:?} {:?}",
+            level,
+            expect_status,
+            expect_stdout,
+            output.status.code(),
+            stdout
+        );
+    }
+}
+
+// links the program like execute_output_test, and checks its exit status and what it printed
+// to stderr
+#[allow(dead_code)]
+pub fn execute_stderr_test(input: &str, checked: bool, expect_status: i32, expect_stderr: &str) {
+    for level in OptimizationLevel::all().iter() {
+        let mut lexer = Lexer::new(&input);
+
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program();
+        if parser.has_error() {
+            panic!("{}", parser.emit_error());
+        }
+
+        let mut eval = Eval::new();
+        eval.checked = checked;
+        eval.file_name = "test.mr".to_string();
+        eval.entry_eval_program(program, &mut Environment::new());
+        if eval.has_error() {
+            panic!("{}", eval.emit_error());
+        }
+        let output = run_test_executable(&eval, *level, &TEST_ARGUMENTS[1..]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(
+            output.status.code() == Some(expect_status) && stderr == expect_stderr,
+            "test failed at {:?}. \nexpected: {} {:?} \nactual: {:?} {:?}",
+            level,
+            expect_status,
+            expect_stderr,
+            output.status.code(),
+            stderr
+        );
+    }
+}
+
+#[test]
+fn return_ident_int() {
+    let input = r#"
+    let a = 1;
+    return a;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn call_function_return_int() {
+    let input = r#"
+    let a = fn(): int {
+      return 3;
+    };
+    return a();
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn if_only() {
+    let input = r#"
+    let mut a = 1;
+    if (false) {
+      a = 2;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn if_true_statement() {
+    let input = r#"
+    let mut a = 1;
+    if (true) {
+      a = 2;
+    } else {
+      a = 3;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 2);
+}
+
+#[test]
+fn if_false_statement() {
+    let input = r#"
+    let mut a = 1;
+    if (false) {
+      a = 2;
+    } else {
+      a = 3;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn if_elseif_statement() {
+    let input = r#"
+    let mut a = 1;
+    if (false) {
+      a = 2;
+    } elseif (true) {
+      a = 3;
+    } else {
+      a = 4;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn while_statement() {
+    let input = r#"
+    let mut a = 0;
+    while (a < 3) {
+      a = a + 1;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn arithmetic() {
+    let input = r#"
+    let mut a = 1 + 2 * (3 + 4);
+    if (a == 15) {
+      a = 1
+    }
+    return a;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn assign_array_element() {
+    let input = r#"
+    let mut a = [1,2,3];
+    a[1] = 10;
+    return a[1];
+"#;
+    execute_eval_test(input, 10);
+}
+
+#[test]
+fn array_bindings_are_copies() {
+    let input = r#"
+    let add = fn(n: int): int {
+      let mut a = [0, 0];
+      a[0] = a[0] + n;
+      return a[0];
+    };
+    let first = add(3);
+    let mut b = [1, 2];
+    b = [5, 6];
+    b[1] = 7;
+    let c = [5, 6];
+    return first + add(4) + b[0] + b[1] + c[1];
+"#;
+    execute_eval_test(input, 25);
+}
+
+#[test]
+fn array_length() {
+    let input = r#"
+    let a = [1, 2];
+    return length(a);
+"#;
+    execute_eval_test(input, 2);
+}
+
+#[test]
+fn parameter_array_element() {
+    let input = r#"
+    let abc = fn(arg: int): int {
+      return arg + 3;
+    };
+    
+    let a = [1, 2];
+    return abc(a[1]);
+"#;
+    execute_eval_test(input, 5);
+}
+
+#[test]
+fn return_void_function() {
+    let input = r#"
+    let abc = fn(): void {
+    };
+    abc();
+    return 5;
+"#;
+    execute_eval_test(input, 5);
+}
+
+#[test]
+fn let_call() {
+    let input = r#"
+    let abc = fn(): int {
+      return 3;
+    };
+    let bbb = abc();
+    return 3;
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn bang_boolean() {
+    let input = r#"
+    let mut a = 0;
+    if (!true) {
+      a = a + 1;
+    } elseif (!false) {
+      a = a + 2;
+    }
+    return 2;
+"#;
+    execute_eval_test(input, 2);
+}
+
+#[test]
+fn equal_boolean_true() {
+    let input = r#"
+    let mut a = 0;
+    if (false == false) {
+      a = a + 1;
+    }
+
+    return a;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn equal_boolean_false() {
+    let input = r#"
+    let mut a = 0;
+    if (true == false) {
+      a = a + 1;
+    } else {
+      a = a + 2;
+    }
+
+    return a;
+"#;
+    execute_eval_test(input, 2);
+}
+
+#[test]
+fn assign_immutable_variable() {
+    let input = r#"
+    let a = 1;
+    a = 2;
+    return a;
+"#;
+    execute_eval_error_test(
+        input,
+        "cannot assign twice to immutable variable a. row: 2, column: 4",
+    );
+}
+
+#[test]
+fn assign_immutable_array_element() {
+    let input = r#"
+    let a = [1, 2, 3];
+    a[1] = 10;
+    return a[1];
+"#;
+    execute_eval_error_test(
+        input,
+        "cannot assign to element of immutable array a. row: 2, column: 4",
+    );
+}
+
+#[test]
+fn assign_mutable_parameter() {
+    let input = r#"
+    let increment = fn(mut x: int): int {
+      x = x + 1;
+      return x;
+    };
+    return increment(2);
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn assign_immutable_parameter() {
+    let input = r#"
+    let increment = fn(x: int): int {
+      x = x + 1;
+      return x;
+    };
+    return increment(2);
+"#;
+    execute_eval_error_test(
+        input,
+        "cannot assign twice to immutable variable x. row: 2, column: 6",
+    );
+}
+
+#[test]
+fn float_arithmetic() {
+    let input = r#"
+    let a = 1.5;
+    let b = a * 2.0 + 0.5;
+    return to_int(b);
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn float_comparison() {
+    let input = r#"
+    let mut a = 0;
+    if (1.5 < 2.5) {
+      a = a + 1;
+    }
+    if (-1e-3 >= 0.0) {
+      a = a + 2;
+    }
+    return a;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn float_parameter() {
+    let input = r#"
+    let half = fn(x: float): float {
+      return x / 2.0;
+    };
+    return to_int(half(to_float(9)));
+"#;
+    execute_eval_test(input, 4);
+}
+
+#[test]
+fn float_not_equal_nan() {
+    let input = r#"
+    let zero = 0.0;
+    let nan = zero / zero;
+    let mut result = 0;
+    if (nan != nan) {
+        result = result + 1;
+    }
+    if (nan == nan) {
+        result = result + 10;
+    }
+    if (1.5 != 1.5) {
+        result = result + 100;
+    }
+    return result;
+"#;
+    execute_eval_test(input, 1);
+}
+
+#[test]
+fn float_cast_arguments() {
+    let input = r#"
+    return to_int();
+"#;
+    execute_eval_error_test(
+        input,
+        "to_int takes 1 argument, but got 0. row: 1, column: 11",
+    );
+
+    let input = r#"
+    return to_int(to_float(1, 2));
+"#;
+    execute_eval_error_test(
+        input,
+        "to_float takes 1 argument, but got 2. row: 1, column: 18",
+    );
+}
+
+#[test]
+fn float_mixed_with_integer() {
+    let input = r#"
+    let a = 1.5 + 1;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "right value should be float, but actually Integer. row: 1, column: 16",
+    );
+}
+
+#[test]
+fn printf_float() {
+    let input = r#"
+    printf("%f\n", 1.5 * 3.0);
+    return 0;
+"#;
+    execute_output_test(input, 0, "4.500000\n");
+}
+
+#[test]
+fn sized_integer_wrapping() {
+    let input = r#"
+    let a = 200u8;
+    let b = a + 100;
+    return b as int;
+"#;
+    execute_eval_test(input, 44);
+}
+
+#[test]
+fn sized_integer_signed_division() {
+    let input = r#"
+    let a = -9i64;
+    let mut b = 0i64;
+    if (a < 0) {
+        b = a / 2 * -1;
+    }
+    return b as int;
+"#;
+    execute_eval_test(input, 4);
+}
+
+#[test]
+fn sized_integer_parameter() {
+    let input = r#"
+    let widen = fn(x: u8): u64 {
+        return x as u64 * 1000;
+    };
+    return (widen(255) / 1000) as int;
+"#;
+    execute_eval_test(input, 255);
+}
+
+#[test]
+fn cast_float_to_integer() {
+    let input = r#"
+    let a = 7.9 as u16;
+    return a as int;
+"#;
+    execute_eval_test(input, 7);
+}
+
+#[test]
+fn sized_integer_mismatch() {
+    let input = r#"
+    let a = 1u8;
+    let b = 2i64;
+    let c = a + b;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "right value should be u8, but actually i64. row: 3, column: 14",
+    );
+}
+
+#[test]
+fn sized_integer_literal_out_of_range() {
+    let input = r#"
+    let a = 1u8 + 300;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "integer literal 300 does not fit in u8. row: 1, column: 18",
+    );
+}
+
+#[test]
+fn radix_integer_literal() {
+    let input = r#"
+    let a = -128i8;
+    return 0x10 + 0b11 + 0o7 + 1_000 + (a + 1_0) as int;
+"#;
+    execute_eval_test(input, 908);
+}
+
+#[test]
+fn printf_string_escape() {
+    let input = r#"
+    printf("\"%d\"\t\u{3042}\x21\n", 1);
+    return 0;
+"#;
+    execute_output_test(input, 0, "\"1\"\t\u{3042}!\n");
+}
+
+#[test]
+fn unicode_identifier_and_string() {
+    let input = r#"
+    let 挨拶 = "こんにちは🌏\n";
+    printf(挨拶);
+    let 数 = 3;
+    return 数;
+"#;
+    execute_eval_test(input, 3);
+}
+
+#[test]
+fn string_concat_and_len() {
+    let input = r#"
+    let mut s = "ab";
+    s = s + "cde";
+    printf("%s\n", s);
+    return len(s);
+"#;
+    execute_eval_test(input, 5);
+}
+
+#[test]
+fn string_comparison() {
+    let input = r#"
+    let a = "abc";
+    let mut result = 0;
+    if (a == "ab" + "c") {
+        result = result + 1;
+    }
+    if (a != "abd") {
+        result = result + 10;
+    }
+    if (a < "abd") {
+        result = result + 100;
+    }
+    // the bytes after \0 are compared too, and a prefix is less than the longer string
+    if ("a\0b" != "a\0c") {
+        result = result + 1000;
+    }
+    if ("a\0b" < "a\0c") {
+        result = result + 10000;
+    }
+    if ("a\0" < "a\0b") {
+        result = result + 100000;
+    }
+    if ("a\0b" == "a\0" + "b") {
+        result = result + 1000000;
+    }
+    return result;
+"#;
+    execute_eval_test(input, 1111111);
+}
+
+#[test]
+fn string_index_and_slice() {
+    let input = r#"
+    let s = "hello, world";
+    let world = s[7:];
+    let hello = s[:5];
+    printf("%s %s\n", hello, world);
+    return s[1] as int + len(s[2:4]) + len(world);
+"#;
+    execute_eval_test(input, 101 + 2 + 5);
+}
+
+#[test]
+fn string_out_of_bounds() {
+    let input = r#"
+    let s = "abc";
+    let i = 3;
+    return s[i] as int;
+"#;
+    execute_stderr_test(
+        input,
+        false,
+        101,
+        "test.mr:4:12: index 3 out of bounds for length 3\n",
+    );
+
+    let input = r#"
+    let s = "abc";
+    let start = 2;
+    let t = s[start:1];
+    return 0;
+"#;
+    execute_stderr_test(
+        input,
+        false,
+        101,
+        "test.mr:4:13: slice 2:1 out of bounds for length 3\n",
+    );
+
+    let input = r#"
+    return len(1);
+"#;
+    execute_eval_error_test(
+        input,
+        "len expects (value: string) or (value: array). row: 1, column: 11",
+    );
+}
+
+#[test]
+fn string_parameter_and_return() {
+    let input = r#"
+    let greet = fn(name: string): string {
+        return "hi " + name;
+    };
+    return len(greet("bob"));
+"#;
+    execute_eval_test(input, 6);
+}
+
+#[test]
+fn string_unsupported_operator() {
+    let input = r#"
+    let a = "a" - "b";
+    return 0;
+"#;
+    execute_eval_error_test(input, "- cannot be used for string. row: 1, column: 16");
+}
+
+#[test]
+fn string_interpolation() {
+    let input = r#"
+    let n = 3;
+    let name = "apples";
+    let ok = n > 2;
+    let s = "${n} ${name}: ${ok} ${1.5} ${-200i64} ${200u8} 100%";
+    let mut result = len(s);
+    if (s == "3 apples: true 1.5 -200 200 100%") {
+        result = result + 100;
+    }
+    return result;
+"#;
+    execute_eval_test(input, 132);
+}
+
+#[test]
+fn print_interpolation() {
+    let input = r#"
+    let n = 3;
+    let name = "apples";
+    printf("${n} ${name} for ${n > 5} 100%\n");
+    printf("\${n} is ${n}\n");
+    return n;
+"#;
+    execute_output_test(input, 3, "3 apples for false 100%\n${n} is 3\n");
+
+    let input = r#"
+    let n = 3;
+    printf("${n} %d\n", n);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "printf takes no arguments after an interpolated string, but got 1. row: 2, column: 4",
+    );
+}
+
+#[test]
+fn interpolate_unsupported_value() {
+    let input = r#"
+    let s = "${[1, 2]}";
+    return 0;
+"#;
+    execute_eval_error_test(input, "[1, 2] cannot be interpolated. row: 1, column: 12");
+}
+
+#[test]
+fn printf_format_mismatch() {
+    let input = r#"
+    let n = 3;
+    printf("%d: %s\n", n, n + 1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "printf conversion %s at row: 2, column: 16 expects a string, but (n + 1) is Integer. row: 2, column: 28",
+    );
+
+    let input = r#"
+    printf("%d %lld\n", 1, 2);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "printf conversion %lld at row: 1, column: 15 expects a 64-bit integer, but 2 is Integer. row: 1, column: 27",
+    );
+
+    let input = r#"
+    printf("%d %d\n", 1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "printf format expects 2 arguments, but got 1. row: 1, column: 4",
+    );
+
+    let input = r#"
+    printf("\t%q\n", 1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "unknown conversion %q in printf format. row: 1, column: 14",
     );
 }
 
 #[test]
-fn return_ident_int() {
+fn printf_star_pointer_and_boolean() {
     let input = r#"
-    let a = 1;
-    return a;
+    let width = 5;
+    let s = "abc";
+    printf("[%*d] [%-*.*s] %d %d\n", width, 42, width, 2, s, 1 > 0, 1 < 0);
+    return 0;
 "#;
-    execute_eval_test(input, 1);
+    execute_output_test(input, 0, "[   42] [ab   ] 1 0\n");
+
+    // the address differs between runs, so only the status is checked
+    let input = r#"
+    let s = "abc";
+    let a = [1, 2];
+    printf("%p %p\n", s, a);
+    return 7;
+"#;
+    execute_eval_test(input, 7);
 }
 
 #[test]
-fn call_function_return_int() {
+fn print_values() {
     let input = r#"
-    let a = fn(): int {
-      return 3;
-    };
-    return a();
+    let numbers = [1, 2, 3];
+    let flags = [true, false];
+    print(-5);
+    print(" ");
+    println(2u8);
+    println(1.5);
+    println(numbers[0] < 2);
+    println("text");
+    println(numbers);
+    println(flags);
+    println();
+    return len("${numbers[2]}");
 "#;
-    execute_eval_test(input, 3);
+    execute_output_test(
+        input,
+        1,
+        "-5 2\n1.5\ntrue\ntext\n[1, 2, 3]\n[true, false]\n\n",
+    );
 }
 
 #[test]
-fn if_only() {
+fn print_unsupported_value() {
     let input = r#"
-    let a = 1;
-    if (false) {
-      a = 2;
-    }
-    return a;
+    let f = fn(): int { return 1; };
+    println(f);
+    return 0;
 "#;
-    execute_eval_test(input, 1);
+    execute_eval_error_test(input, "f cannot be printed. row: 2, column: 4");
+
+    let input = r#"
+    print(1, 2);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "print takes 1 argument, but got 2. row: 1, column: 4",
+    );
 }
 
 #[test]
-fn if_true_statement() {
+fn read_with_arguments() {
     let input = r#"
-    let a = 1;
-    if (true) {
-      a = 2;
-    } else {
-      a = 3;
-    }
-    return a;
+    let n = read_int(1);
+    return 0;
 "#;
-    execute_eval_test(input, 2);
+    execute_eval_error_test(
+        input,
+        "read_int takes no arguments, but got 1. row: 1, column: 12",
+    );
 }
 
 #[test]
-fn if_false_statement() {
+fn file_input_output() {
+    let directory = std::env::temp_dir();
+    let path = directory.join(format!("file_input_output_{}.txt", std::process::id()));
+    let missing_path = directory.join(format!("missing_{}.txt", std::process::id()));
+    let input = format!(
+        r#"
+    let path = "{}";
+    let writer = open(path, "w");
+    let mut result = write(writer, "first\nsecond");
+    close(writer);
+    if (exists(path)) {{
+        result = result + 100;
+    }}
+    let reader = open(path, "r");
+    let contents = read_all(reader);
+    if (read_ok()) {{
+        if (contents == "first\nsecond") {{
+            result = result + 1000;
+        }}
+    }}
+    close(reader);
+    let missing = open("{}", "r");
+    if (close(missing) == -1) {{
+        result = result + 10000;
+    }}
+    read_all(missing);
+    if (!read_ok()) {{
+        result = result + 100000;
+    }}
+    return result;
+"#,
+        path.display(),
+        missing_path.display()
+    );
+    execute_eval_test(&input, 111112);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn closed_file_handles() {
+    let path = std::env::temp_dir().join(format!("closed_file_handles_{}.txt", std::process::id()));
+    let input = format!(
+        r#"
+    let path = "{}";
+    let first = open(path, "w");
+    close(first);
+    let second = open(path, "w");
+    let mut result = 0;
+    if (write(first, "stale") == -1) {{
+        result = result + 1;
+    }}
+    if (close(first) == -1) {{
+        result = result + 10;
+    }}
+    if (write(second + 256, "made up") == -1) {{
+        result = result + 100;
+    }}
+    if (write(second, "text") == 4) {{
+        result = result + 1000;
+    }}
+    if (close(second) == 0) {{
+        result = result + 10000;
+    }}
+    return result;
+"#,
+        path.display()
+    );
+    execute_eval_test(&input, 11111);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn location_at_end_of_line() {
     let input = r#"
-    let a = 1;
-    if (false) {
-      a = 2;
-    } else {
-      a = 3;
-    }
-    return a;
+    let a = 1 + b
+    ;
+    return 0;
 "#;
-    execute_eval_test(input, 3);
+    execute_eval_error_test(input, "b is not found. row: 1, column: 16");
 }
 
 #[test]
-fn if_elseif_statement() {
+fn file_argument_types() {
     let input = r#"
-    let a = 1;
-    if (false) {
-      a = 2;
-    } elseif (true) {
-      a = 3;
-    } else {
-      a = 4;
-    }
-    return a;
+    write(1, "text");
+    return 0;
 "#;
-    execute_eval_test(input, 3);
+    execute_eval_error_test(
+        input,
+        "write expects (file: i64, text: string). row: 1, column: 4",
+    );
 }
 
 #[test]
-fn while_statement() {
+fn command_line_arguments() {
     let input = r#"
-    let a = 0;
-    while (a < 3) {
-      a = a + 1;
+    let second = fn(): string {
+        return argv(1);
+    };
+    let mut result = argc() * 1000 + len(second());
+    if (argv(0) == "test") {
+        result = result + 100;
     }
-    return a;
+    if (len(argv(2)) + len(argv(-1)) == 0) {
+        result = result + 10000;
+    }
+    return result;
 "#;
-    execute_eval_test(input, 3);
+    execute_eval_test(input, 12109);
 }
 
 #[test]
-fn arithmetic() {
+fn environment_variables() {
+    std::env::set_var("ENVIRONMENT_VARIABLES_TEST", "value");
     let input = r#"
-    let a = 1 + 2 * (3 + 4);
-    if (a == 15) {
-      a = 1
+    let mut result = len(getenv("ENVIRONMENT_VARIABLES_TEST"));
+    if (getenv("ENVIRONMENT_VARIABLES_TEST_UNSET") == "") {
+        result = result + 100;
     }
-    return a;
+    return result;
 "#;
-    execute_eval_test(input, 1);
+    execute_eval_test(input, 105);
+
+    let input = r#"
+    exit("1");
+    return 0;
+"#;
+    execute_eval_error_test(input, "exit expects (code: int). row: 1, column: 4");
 }
 
 #[test]
-fn assign_array_element() {
+fn exit_status() {
     let input = r#"
-    let a = [1,2,3];
-    a[1] = 10;
-    return a[1];
+    let stop = fn(code: int): int {
+        printf("stopping\n");
+        exit(code);
+        printf("not printed\n");
+        return 0;
+    };
+    stop(7);
+    printf("not printed\n");
+    return 0;
 "#;
-    execute_eval_test(input, 10);
+    execute_output_test(input, 7, "stopping\n");
 }
 
 #[test]
-fn array_length() {
+fn checked_array_bounds() {
     let input = r#"
-    let a = [1, 2];
-    return length(a);
+    let mut a = [1, 2, 3];
+    let i = 2;
+    a[i] = a[i - 2] + a[1];
+    return a[i];
 "#;
-    execute_eval_test(input, 2);
+    execute_stderr_test(input, true, 3, "");
+
+    let input = r#"
+    let a = [1, 2, 3];
+    let i = 5;
+    return a[i];
+"#;
+    execute_stderr_test(
+        input,
+        true,
+        101,
+        "test.mr:4:12: index 5 out of bounds for length 3\n",
+    );
+
+    let input = r#"
+    let mut a = [1, 2, 3];
+    a[-1] = 0;
+    return 0;
+"#;
+    execute_stderr_test(
+        input,
+        true,
+        101,
+        "test.mr:3:5: index -1 out of bounds for length 3\n",
+    );
+
+    let input = r#"
+    let s = "abc";
+    let t = s[2:5];
+    return 0;
+"#;
+    execute_stderr_test(
+        input,
+        true,
+        101,
+        "test.mr:3:13: slice 2:5 out of bounds for length 3\n",
+    );
 }
 
 #[test]
-fn parameter_array_element() {
+fn checked_arithmetic() {
     let input = r#"
-    let abc = fn(arg: int): int {
-      return arg + 3;
-    };
-    
-    let a = [1, 2];
-    return abc(a[1]);
+    let a = 6;
+    let zero = a - 6;
+    return a / zero;
 "#;
-    execute_eval_test(input, 5);
+    execute_stderr_test(input, true, 101, "test.mr:4:14: division by zero\n");
+
+    let input = r#"
+    let a = 2147483647;
+    return a + 1;
+"#;
+    execute_stderr_test(input, true, 101, "test.mr:3:14: i32 overflow in +\n");
+
+    let input = r#"
+    let a = 200u8;
+    let b = a * 2u8;
+    return 0;
+"#;
+    execute_stderr_test(input, true, 101, "test.mr:3:15: u8 overflow in *\n");
+
+    let input = r#"
+    let a = -2147483647 - 1;
+    return a - 1;
+"#;
+    execute_stderr_test(input, true, 101, "test.mr:3:14: i32 overflow in -\n");
+
+    let input = r#"
+    let a = 1u32;
+    return (a - 2u32) as int;
+"#;
+    execute_stderr_test(input, true, 101, "test.mr:3:15: u32 overflow in -\n");
+
+    let input = r#"
+    let a = 65536i64 * 65536i64;
+    let b = a * a;
+    return 0;
+"#;
+    execute_stderr_test(input, true, 101, "test.mr:3:15: i64 overflow in *\n");
+
+    let input = r#"
+    let a = -32768;
+    let b = a * 65536;
+    return (b == -2147483647 - 1) as int;
+"#;
+    execute_stderr_test(input, true, 1, "");
 }
 
 #[test]
-fn return_void_function() {
+fn assert_and_panic() {
     let input = r#"
-    let abc = fn(): void {
+    let x = 3;
+    assert(x == 3);
+    assert(x > 1, "x is small");
+    return x;
+"#;
+    execute_stderr_test(input, true, 3, "");
+
+    let input = r#"
+    let x = 3;
+    assert(x % 2 == 0);
+    return 0;
+"#;
+    execute_stderr_test(
+        input,
+        true,
+        102,
+        "test.mr:3:5: assertion failed: ((x % 2) == 0)\n",
+    );
+
+    let input = r#"
+    let x = 3;
+    assert(x < 0, "x is ${x}");
+    return 0;
+"#;
+    execute_stderr_test(
+        input,
+        true,
+        102,
+        "test.mr:3:5: assertion failed: (x < 0): x is 3\n",
+    );
+
+    let input = r#"
+    let f = fn(): int {
+        panic("unreachable");
+        return 0;
     };
-    abc();
-    return 5;
+    return f();
 "#;
-    execute_eval_test(input, 5);
-}
+    execute_stderr_test(input, true, 102, "test.mr:3:9: panic: unreachable\n");
 
-#[test]
-fn let_call() {
     let input = r#"
-    let abc = fn(): int {
-      return 3;
+    let note = fn(): string {
+        printf("evaluated\n");
+        return "note";
     };
-    let bbb = abc();
-    return 3;
+    let x = 3;
+    assert(x == 3, note());
+    printf("passed\n");
+    assert(x == 4, note());
+    return 0;
 "#;
-    execute_eval_test(input, 3);
+    execute_output_test(input, 102, "passed\nevaluated\n");
+
+    let input = r#"
+    assert(1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "assert expects (condition: boolean) or (condition: boolean, message: string). row: 1, column: 4",
+    );
 }
 
 #[test]
-fn bang_boolean() {
+fn math_built_ins() {
     let input = r#"
-    let a = 0;
-    if (!true) {
-      a = a + 1;
-    } elseif (!false) {
-      a = a + 2;
+    let a = abs(-7) + min(3, -2) + max(4u8, 9u8) as int;
+    let b = pow(3, 4) + sqrt(99) + sqrt(-1);
+    return a + b;
+"#;
+    execute_eval_test(input, 7 - 2 + 9 + 81 + 9);
+
+    let input = r#"
+    let a = sqrt(2.25) * pow(2.0, 3.0) + abs(-0.5);
+    return to_int(max(a, min(1.0, 2.0)) * 10.0);
+"#;
+    execute_eval_test(input, 125);
+
+    // abs clears the sign of -0.0, and min and max skip nan
+    let input = r#"
+    let zero = 0.0;
+    let nan = zero / zero;
+    let mut result = 0;
+    if (1.0 / abs(-zero) > 0.0) {
+        result = result + 1;
     }
-    return 2;
+    if (min(nan, 2.0) == 2.0) {
+        result = result + 10;
+    }
+    if (max(3.0, nan) == 3.0) {
+        result = result + 100;
+    }
+    return result;
 "#;
-    execute_eval_test(input, 2);
+    execute_eval_test(input, 111);
+
+    let input = r#"
+    let a = min(1, 2.0);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "min expects (left: int, right: int) or (left: float, right: float). row: 1, column: 12",
+    );
 }
 
 #[test]
-fn equal_boolean_true() {
+fn power_operator() {
     let input = r#"
-    let a = 0;
-    if (false == false) {
-      a = a + 1;
-    }
+    let a = 2 ** 3 ** 2;
+    let b = -2i64 ** 3;
+    let c = -2 ** 2;
+    return a + 3 * 2 ** 2 + b as int + c * 1000;
+"#;
+    execute_eval_test(input, (512 + 12 - 8 - 4000) as u32 as u64);
 
-    return a;
+    let input = r#"
+    return to_int(2.0 ** 0.5 * 100.0);
+"#;
+    execute_eval_test(input, 141);
+
+    let input = r#"
+    let a = 2 ** 1.5;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "** expects two integers of the same type or two floats, but got Integer and Float. row: 1, column: 14",
+    );
+}
+
+#[test]
+fn seeded_random_numbers() {
+    let input = r#"
+    seed(42);
+    let first = rand();
+    let second = rand();
+    seed(42);
+    let mut result = second - 343404953;
+    if (rand() != first) {
+        result = 100;
+    }
+    let start = clock_ms();
+    if (clock_ms() < start) {
+        result = 100;
+    }
+    return result + rand_range(-10, 10);
 "#;
     execute_eval_test(input, 1);
+
+    let input = r#"
+    rand_range(1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "rand_range expects (low: int, high: int). row: 1, column: 4",
+    );
 }
 
 #[test]
-fn equal_boolean_false() {
+fn export_functions() {
     let input = r#"
-    let a = 0;
-    if (true == false) {
-      a = a + 1;
-    } else {
-      a = a + 2;
+    export fn add(a: int, b: int): int { return a + b; };
+    export fn half(x: float): float { return x / 2.0; };
+    return add(1, 2) + half(4.0) as int;
+"#;
+    execute_eval_test(input, 5);
+
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+    let mut eval = Eval::new();
+    eval.entry_eval_program(program, &mut Environment::new());
+    let names: Vec<&str> = eval.exports.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["add", "half"]);
+    assert!(get_named_function(eval.lc.module, "add").is_null() == false);
+
+    let input = r#"
+    export fn greet(name: string): int { return 0; };
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "export fn greet cannot take string. row: 1, column: 4",
+    );
+
+    let input = r#"
+    export fn printf(): int { return 0; };
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "export fn printf conflicts with an existing symbol. row: 1, column: 4",
+    );
+}
+
+#[test]
+fn runtime_helpers_are_internal() {
+    // the old names of runtime helpers are free for a program
+    let input = r#"
+    export fn rand_next(x: int): int { return x + 1; };
+    let r = rand();
+    println([1, 2]);
+    return rand_next(2);
+"#;
+    execute_eval_test(input, 3);
+
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+    let mut eval = Eval::new();
+    eval.entry_eval_program(program, &mut Environment::new());
+    let module = eval.lc.module;
+    for name in vec!["rand_next", "print_array.int"] {
+        let helper = eval.lc.get_runtime(name).unwrap();
+        let symbol = format!("{}{}", RUNTIME_PREFIX, name);
+        assert_eq!(get_named_function(module, &symbol), helper);
+        assert_eq!(get_linkage(helper), LLVMLinkage::LLVMInternalLinkage);
+    }
+    for name in vec!["rand_state", "argc"] {
+        let helper = eval.lc.get_runtime(name).unwrap();
+        let symbol = format!("{}{}", RUNTIME_PREFIX, name);
+        assert_eq!(get_named_global(module, &symbol), helper);
+        assert_eq!(get_linkage(helper), LLVMLinkage::LLVMInternalLinkage);
     }
+}
 
-    return a;
+#[test]
+fn extern_functions() {
+    let input = r#"
+    extern fn atoi(s: string): int;
+    extern fn strlen(s: string): i64;
+    extern fn getenv(name: string): string;
+    extern fn printf(format: string, ...): int;
+    let written = printf("%d-%s\n", 42u8, "ok");
+    let unset = getenv("RUST_MONKEY_IR_UNSET_VARIABLE");
+    return atoi("30") + strlen("abc") as int + len(unset) + written;
 "#;
-    execute_eval_test(input, 2);
+    execute_eval_test(input, 30 + 3 + 0 + 6);
+
+    let input = r#"
+    extern fn atoi(s: string): int;
+    atoi("1", "2");
+    return 0;
+"#;
+    execute_eval_error_test(input, "atoi takes 1 argument, but got 2. row: 2, column: 4");
+
+    let input = r#"
+    extern fn printf(format: string, ...): int;
+    printf(1);
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "mismatched types: expected string, but actually int. row: 2, column: 4",
+    );
+
+    let input = r#"
+    extern fn strlen(s: string): int;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "extern fn strlen(s: string): int conflicts with an existing declaration of strlen. row: 1, column: 4",
+    );
+
+    let input = r#"
+    extern fn atoi(s: string): int;
+    extern fn atoi(s: string): int;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "extern fn atoi conflicts with an existing symbol. row: 2, column: 4",
+    );
+
+    let input = r#"
+    export fn twice(x: int): int { return x * 2; };
+    extern fn twice(x: int): int;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "extern fn twice conflicts with an existing symbol. row: 2, column: 4",
+    );
+
+    let input = r#"
+    extern fn stderr(): int;
+    return 0;
+"#;
+    execute_eval_error_test(
+        input,
+        "extern fn stderr conflicts with an existing symbol. row: 1, column: 4",
+    );
 }
@@ -0,0 +1,362 @@
+use llvm_sys::core::*;
+use llvm_sys::*;
+
+use parser::expressions::*;
+
+use evaluate_ir::cast::*;
+use evaluate_ir::object::*;
+
+use ir::arithmetic::*;
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::converter::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+use ir::string::*;
+
+// upper bounds of the formatted length, e.g. -9223372036854775808 or -1.79769e+308
+const MAX_INTEGER_LENGTH: u64 = 20;
+const MAX_FLOAT_LENGTH: u64 = 16;
+const MAX_BOOLEAN_LENGTH: u64 = 5;
+
+// `%` is the only character with a meaning in a format
+pub fn escape_format_text(text: &str) -> String {
+    text.replace("%", "%%")
+}
+
+// picks the printf conversion for a value and converts the value to match it.
+// also returns an upper bound of the formatted length. None if the value has no conversion
+pub fn format_object(
+    lc: &mut LLVMCreator,
+    object: Object,
+) -> Option<(&'static str, *mut LLVMValue, *mut LLVMValue)> {
+    let builder = lc.builder;
+    match object {
+        Object::Integer(integer_type, value) => {
+            let max_length = const_int(int32_type(), MAX_INTEGER_LENGTH);
+            match integer_type {
+                IntegerType::I64 => Some(("%lld", value, max_length)),
+                IntegerType::U64 => Some(("%llu", value, max_length)),
+                IntegerType::U32 => Some(("%u", value, max_length)),
+                // variadic arguments narrower than int are promoted like C does
+                _ => {
+                    let value = cast_integer(builder, integer_type, IntegerType::I32, value);
+                    Some(("%d", value, max_length))
+                }
+            }
+        }
+        Object::Float(value) => Some(("%g", value, const_int(int32_type(), MAX_FLOAT_LENGTH))),
+        Object::Boolean(value) => {
+            let true_string = codegen_string(lc, "true", ".str");
+            let false_string = codegen_string(lc, "false", ".str");
+            Some((
+                "%s",
+                build_select(builder, value, true_string, false_string, ""),
+                const_int(int32_type(), MAX_BOOLEAN_LENGTH),
+            ))
+        }
+        Object::String(value) => Some((
+            "%s",
+            get_string_pointer(builder, value),
+            get_string_length(builder, value),
+        )),
+        _ => None,
+    }
+}
+
+// defines `void print_array(T*, i32)` which prints elements as `[1, 2, 3]`.
+// one helper is shared by every array of the same element type
+pub fn get_print_array_function(
+    lc: &mut LLVMCreator,
+    child_type: LLVMExpressionType,
+) -> *mut LLVMValue {
+    let name = format!("print_array.{}", child_type);
+    if let Some(function) = lc.get_runtime(&name) {
+        return function;
+    }
+
+    let element_type = convert_llvm_type(child_type.clone());
+    let mut parameter_types = vec![unsafe { LLVMPointerType(element_type, 0) }, int32_type()];
+    let function_type = function_type(void_type(), &mut parameter_types);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function(&name, function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let loop_block = append_basic_block_in_context(lc.context, function, "");
+    let end_block = append_basic_block_in_context(lc.context, function, "");
+    let printf = lc.built_ins["printf"];
+    let elements = get_param(function, 0);
+    let length = get_param(function, 1);
+
+    build_position_at_end(lc.builder, entry_block);
+    let index = build_alloca(lc.builder, int32_type(), "");
+    build_store(lc.builder, const_int(int32_type(), 0), index);
+    call_function(
+        lc.builder,
+        printf,
+        vec![codegen_string(lc, "[", ".str")],
+        "",
+    );
+    let is_empty = build_int_eq(lc.builder, length, const_int(int32_type(), 0), "");
+    build_cond_br(lc.builder, is_empty, end_block, loop_block);
+
+    build_position_at_end(lc.builder, loop_block);
+    let current_index = build_load(lc.builder, index, "");
+    let is_first = build_int_eq(lc.builder, current_index, const_int(int32_type(), 0), "");
+    let empty_separator = codegen_string(lc, "", ".str");
+    let comma_separator = codegen_string(lc, ", ", ".str");
+    let separator = build_select(lc.builder, is_first, empty_separator, comma_separator, "");
+    let element = build_gep(lc.builder, elements, vec![current_index], "");
+    let element = wrap_llvm_value(child_type, build_load(lc.builder, element, ""));
+    if let Some((conversion, value, _)) = format_object(lc, element) {
+        let format = codegen_string(lc, &format!("%s{}", conversion), ".str");
+        call_function(lc.builder, printf, vec![format, separator, value], "");
+    }
+    let next_index = add_variable(lc.builder, current_index, const_int(int32_type(), 1), "");
+    build_store(lc.builder, next_index, index);
+    let is_last = build_int_eq(lc.builder, next_index, length, "");
+    build_cond_br(lc.builder, is_last, end_block, loop_block);
+
+    build_position_at_end(lc.builder, end_block);
+    call_function(
+        lc.builder,
+        printf,
+        vec![codegen_string(lc, "]", ".str")],
+        "",
+    );
+    build_ret_void(lc.builder);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+// the value printf reads for a conversion
+#[derive(Debug, PartialEq)]
+enum FormatArgument {
+    Integer,
+    LongInteger,
+    Float,
+    String,
+    Pointer,
+}
+
+impl FormatArgument {
+    fn accepts(&self, object: &Object) -> bool {
+        match (self, object) {
+            (FormatArgument::Integer, Object::Integer(integer_type, _)) => {
+                integer_type.bit_width() <= 32
+            }
+            // a boolean is passed as an int, so `%d` prints 1 or 0
+            (FormatArgument::Integer, Object::Boolean(_)) => true,
+            (FormatArgument::LongInteger, Object::Integer(integer_type, _)) => {
+                integer_type.bit_width() == 64
+            }
+            (FormatArgument::Float, Object::Float(_)) => true,
+            (FormatArgument::String, Object::String(_)) => true,
+            (FormatArgument::Pointer, Object::String(_)) => true,
+            (FormatArgument::Pointer, Object::Array(_, _, _)) => true,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FormatArgument::Integer => "a 32-bit or narrower integer",
+            FormatArgument::LongInteger => "a 64-bit integer",
+            FormatArgument::Float => "a float",
+            FormatArgument::String => "a string",
+            FormatArgument::Pointer => "a string or an array",
+        }
+    }
+}
+
+// a conversion such as `%-5lld` and its byte offset in the format.
+// a `*` width or precision is a conversion of its own, because it takes an argument too
+#[derive(Debug, PartialEq)]
+struct Conversion {
+    text: String,
+    offset: usize,
+    argument: FormatArgument,
+}
+
+// an unknown conversion is returned with its offset
+fn parse_conversions(format: &str) -> Result<Vec<Conversion>, (String, usize)> {
+    let bytes = format.as_bytes();
+    let mut conversions = Vec::new();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        if bytes[position] != b'%' {
+            position += 1;
+            continue;
+        }
+        let offset = position;
+        position += 1;
+        if bytes.get(position) == Some(&b'%') {
+            position += 1;
+            continue;
+        }
+
+        // flags, width and precision
+        while position < bytes.len() && b"-+ #0123456789.*".contains(&bytes[position]) {
+            if bytes[position] == b'*' {
+                conversions.push(Conversion {
+                    text: "*".to_string(),
+                    offset: position,
+                    argument: FormatArgument::Integer,
+                });
+            }
+            position += 1;
+        }
+        let mut long_count = 0;
+        while position < bytes.len() && b"hl".contains(&bytes[position]) {
+            if bytes[position] == b'l' {
+                long_count += 1;
+            }
+            position += 1;
+        }
+
+        let argument = match (bytes.get(position), long_count) {
+            (Some(b'd'), 0)
+            | (Some(b'i'), 0)
+            | (Some(b'u'), 0)
+            | (Some(b'x'), 0)
+            | (Some(b'X'), 0)
+            | (Some(b'o'), 0)
+            | (Some(b'c'), 0) => FormatArgument::Integer,
+            (Some(b'd'), _)
+            | (Some(b'i'), _)
+            | (Some(b'u'), _)
+            | (Some(b'x'), _)
+            | (Some(b'X'), _)
+            | (Some(b'o'), _) => FormatArgument::LongInteger,
+            (Some(b'f'), _)
+            | (Some(b'F'), _)
+            | (Some(b'e'), _)
+            | (Some(b'E'), _)
+            | (Some(b'g'), _)
+            | (Some(b'G'), _) => FormatArgument::Float,
+            (Some(b's'), 0) => FormatArgument::String,
+            (Some(b'p'), 0) => FormatArgument::Pointer,
+            _ => {
+                let end = (position + 1).min(bytes.len());
+                return Err((
+                    format!(
+                        "unknown conversion {} in printf format",
+                        String::from_utf8_lossy(&bytes[offset..end])
+                    ),
+                    offset,
+                ));
+            }
+        };
+        position += 1;
+        conversions.push(Conversion {
+            text: String::from_utf8_lossy(&bytes[offset..position]).to_string(),
+            offset: offset,
+            argument: argument,
+        });
+    }
+    Ok(conversions)
+}
+
+// where a byte of a literal format is in the source. escapes are counted as they are usually
+// written, e.g. a newline as `\n`
+fn locate_format_offset(format: &str, offset: usize, location: &Location) -> Location {
+    // the opening quote
+    let mut column = location.column + 1;
+    let mut chars = format[..offset].chars().peekable();
+    while let Some(c) = chars.next() {
+        column += match c {
+            '\n' | '\r' | '\t' | '\0' | '\\' | '"' => 2,
+            // `${` would start an interpolation unless it is written `\${`
+            '$' if chars.peek() == Some(&'{') => 2,
+            c if c.is_ascii_control() => 4,
+            _ => 1,
+        };
+    }
+    Location::new(location.row, column)
+}
+
+// checks the conversions of a literal printf format against the evaluated arguments.
+// errors are reported at the conversion and at the argument
+pub fn check_format(
+    format: &str,
+    format_location: &Location,
+    arguments: &[Object],
+    expressions: &[Expression],
+    location: &Location,
+) -> Result<(), Object> {
+    let conversions = match parse_conversions(format) {
+        Ok(conversions) => conversions,
+        Err((message, offset)) => {
+            let conversion_location = locate_format_offset(format, offset, format_location);
+            return Err(Object::Error(format!(
+                "{}. row: {}, column: {}",
+                message, conversion_location.row, conversion_location.column
+            )));
+        }
+    };
+    if conversions.len() != arguments.len() {
+        return Err(Object::Error(format!(
+            "printf format expects {} arguments, but got {}. row: {}, column: {}",
+            conversions.len(),
+            arguments.len(),
+            location.row,
+            location.column
+        )));
+    }
+
+    for ((conversion, argument), expression) in conversions.iter().zip(arguments).zip(expressions) {
+        if !conversion.argument.accepts(argument) {
+            let conversion_location =
+                locate_format_offset(format, conversion.offset, format_location);
+            let argument_location = expression.location().unwrap_or(location);
+            return Err(Object::Error(format!(
+                "printf conversion {} at row: {}, column: {} expects {}, but {} is {}. row: {}, column: {}",
+                conversion.text,
+                conversion_location.row,
+                conversion_location.column,
+                conversion.argument.name(),
+                expression.string(),
+                argument,
+                argument_location.row,
+                argument_location.column
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_format_conversions() {
+    let conversion = |text: &str, offset: usize, argument: FormatArgument| Conversion {
+        text: text.to_string(),
+        offset: offset,
+        argument: argument,
+    };
+    assert_eq!(
+        parse_conversions("%d%% %-5.2f %lld %s%c %*.*d %p"),
+        Ok(vec![
+            conversion("%d", 0, FormatArgument::Integer),
+            conversion("%-5.2f", 5, FormatArgument::Float),
+            conversion("%lld", 12, FormatArgument::LongInteger),
+            conversion("%s", 17, FormatArgument::String),
+            conversion("%c", 19, FormatArgument::Integer),
+            conversion("*", 23, FormatArgument::Integer),
+            conversion("*", 25, FormatArgument::Integer),
+            conversion("%*.*d", 22, FormatArgument::Integer),
+            conversion("%p", 28, FormatArgument::Pointer),
+        ])
+    );
+    assert_eq!(
+        parse_conversions("ab %q"),
+        Err(("unknown conversion %q in printf format".to_string(), 3))
+    );
+    let location = Location::new(2, 11);
+    assert_eq!(
+        locate_format_offset("a\t${b}%d", 6, &location),
+        Location::new(2, 20)
+    );
+}
@@ -0,0 +1,133 @@
+use parser::expressions::*;
+
+// a function defined by `export fn`. its symbol is the name itself
+#[derive(PartialEq, Clone, Debug)]
+pub struct ExportedFunction {
+    pub name: String,
+    pub parameters: Vec<String>,
+    pub parameter_types: Vec<LLVMExpressionType>,
+    pub return_type: LLVMExpressionType,
+}
+
+impl ExportedFunction {
+    // `int32_t add(int32_t a, int32_t b)`
+    pub fn c_declaration(&self) -> String {
+        let parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .zip(self.parameter_types.iter())
+            .map(|(name, parameter_type)| {
+                format!("{} {}", c_type_name(parameter_type).unwrap_or("void"), name)
+            })
+            .collect();
+        let parameters = if parameters.is_empty() {
+            "void".to_string()
+        } else {
+            parameters.join(", ")
+        };
+        format!(
+            "{} {}({})",
+            c_type_name(&self.return_type).unwrap_or("void"),
+            self.name,
+            parameters
+        )
+    }
+}
+
+// the C type of a value crossing an exported function. null is only valid as a return type
+pub fn c_type_name(expression_type: &LLVMExpressionType) -> Option<&'static str> {
+    match expression_type {
+        LLVMExpressionType::Integer(IntegerType::I8) => Some("int8_t"),
+        LLVMExpressionType::Integer(IntegerType::I16) => Some("int16_t"),
+        LLVMExpressionType::Integer(IntegerType::I32) => Some("int32_t"),
+        LLVMExpressionType::Integer(IntegerType::I64) => Some("int64_t"),
+        LLVMExpressionType::Integer(IntegerType::U8) => Some("uint8_t"),
+        LLVMExpressionType::Integer(IntegerType::U16) => Some("uint16_t"),
+        LLVMExpressionType::Integer(IntegerType::U32) => Some("uint32_t"),
+        LLVMExpressionType::Integer(IntegerType::U64) => Some("uint64_t"),
+        LLVMExpressionType::Float => Some("double"),
+        LLVMExpressionType::Boolean => Some("bool"),
+        LLVMExpressionType::Null => Some("void"),
+        _ => None,
+    }
+}
+
+// `lib/add-numbers.h` is guarded by `ADD_NUMBERS_H`
+pub fn header_guard(file_stem: &str) -> String {
+    let guard: String = file_stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_H", guard)
+}
+
+pub fn build_header(file_stem: &str, exports: &[ExportedFunction]) -> String {
+    let guard = header_guard(file_stem);
+    let mut header = String::new();
+    header.push_str(&format!("#ifndef {}\n#define {}\n\n", guard, guard));
+    header.push_str("#include <stdbool.h>\n#include <stdint.h>\n\n");
+    header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+    for export in exports {
+        header.push_str(&format!("{};\n", export.c_declaration()));
+    }
+    if exports.is_empty() == false {
+        header.push_str("\n");
+    }
+    header.push_str("#ifdef __cplusplus\n}\n#endif\n\n");
+    header.push_str(&format!("#endif /* {} */\n", guard));
+    header
+}
+
+#[test]
+fn header() {
+    let exports = vec![
+        ExportedFunction {
+            name: "add".to_string(),
+            parameters: vec!["a".to_string(), "b".to_string()],
+            parameter_types: vec![
+                LLVMExpressionType::Integer(IntegerType::I32),
+                LLVMExpressionType::Integer(IntegerType::U64),
+            ],
+            return_type: LLVMExpressionType::Integer(IntegerType::I64),
+        },
+        ExportedFunction {
+            name: "reset".to_string(),
+            parameters: vec![],
+            parameter_types: vec![],
+            return_type: LLVMExpressionType::Null,
+        },
+        ExportedFunction {
+            name: "scale".to_string(),
+            parameters: vec!["x".to_string(), "enabled".to_string()],
+            parameter_types: vec![LLVMExpressionType::Float, LLVMExpressionType::Boolean],
+            return_type: LLVMExpressionType::Float,
+        },
+    ];
+    let expected = r#"#ifndef ADD_NUMBERS_H
+#define ADD_NUMBERS_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+int64_t add(int32_t a, uint64_t b);
+void reset(void);
+double scale(double x, bool enabled);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ADD_NUMBERS_H */
+"#;
+    assert_eq!(build_header("add-numbers", &exports), expected);
+}
@@ -7,28 +7,72 @@ use evaluate_ir::object::*;
 
 use ir::arithmetic::*;
 use ir::condition::*;
+use ir::const_value::*;
 use ir::converter::*;
+use ir::creator::*;
 use ir::function::*;
+use ir::llvm_type::*;
+use ir::string::*;
 
 pub fn calculate_infix_integer(
     builder: *mut LLVMBuilder,
     infix: Infix,
+    integer_type: IntegerType,
     left: *mut LLVMValue,
     right: *mut LLVMValue,
     _location: Location,
 ) -> Object {
+    let signed = integer_type.is_signed();
     match infix {
-        Infix::Plus => Object::Integer(add_variable(builder, left, right, "")),
-        Infix::Minus => Object::Integer(sub_variable(builder, left, right, "")),
-        Infix::Multiply => Object::Integer(multiple_variable(builder, left, right, "")),
-        Infix::Rem => Object::Integer(rem_variable(builder, left, right, "")),
-        Infix::Divide => Object::Integer(divide_variable(builder, left, right, "")),
+        Infix::Plus => Object::Integer(integer_type, add_variable(builder, left, right, "")),
+        Infix::Minus => Object::Integer(integer_type, sub_variable(builder, left, right, "")),
+        Infix::Multiply => {
+            Object::Integer(integer_type, multiple_variable(builder, left, right, ""))
+        }
+        Infix::Rem if signed => {
+            Object::Integer(integer_type, signed_rem_variable(builder, left, right, ""))
+        }
+        Infix::Rem => Object::Integer(integer_type, rem_variable(builder, left, right, "")),
+        Infix::Divide if signed => Object::Integer(
+            integer_type,
+            signed_divide_variable(builder, left, right, ""),
+        ),
+        Infix::Divide => Object::Integer(integer_type, divide_variable(builder, left, right, "")),
+        Infix::Lt if signed => Object::Boolean(build_int_slt(builder, left, right, "")),
         Infix::Lt => Object::Boolean(build_int_ult(builder, left, right, "")),
+        Infix::Lte if signed => Object::Boolean(build_int_sle(builder, left, right, "")),
         Infix::Lte => Object::Boolean(build_int_ule(builder, left, right, "")),
+        Infix::Gt if signed => Object::Boolean(build_int_sgt(builder, left, right, "")),
         Infix::Gt => Object::Boolean(build_int_ugt(builder, left, right, "")),
+        Infix::Gte if signed => Object::Boolean(build_int_sge(builder, left, right, "")),
         Infix::Gte => Object::Boolean(build_int_uge(builder, left, right, "")),
         Infix::Eq => Object::Boolean(build_int_eq(builder, left, right, "")),
         Infix::NotEq => Object::Boolean(build_int_ne(builder, left, right, "")),
+        Infix::Power => unreachable!("** is built by build_power"),
+    }
+}
+
+pub fn calculate_infix_float(
+    builder: *mut LLVMBuilder,
+    infix: Infix,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+    _location: Location,
+) -> Object {
+    match infix {
+        Infix::Plus => Object::Float(add_float_variable(builder, left, right, "")),
+        Infix::Minus => Object::Float(sub_float_variable(builder, left, right, "")),
+        Infix::Multiply => Object::Float(multiple_float_variable(builder, left, right, "")),
+        Infix::Rem => Object::Float(rem_float_variable(builder, left, right, "")),
+        Infix::Divide => Object::Float(divide_float_variable(builder, left, right, "")),
+        Infix::Lt => Object::Boolean(build_float_olt(builder, left, right, "")),
+        Infix::Lte => Object::Boolean(build_float_ole(builder, left, right, "")),
+        Infix::Gt => Object::Boolean(build_float_ogt(builder, left, right, "")),
+        Infix::Gte => Object::Boolean(build_float_oge(builder, left, right, "")),
+        Infix::Eq => Object::Boolean(build_float_oeq(builder, left, right, "")),
+        // unordered, so that `!=` is true for nan like the negation of `==`
+        Infix::NotEq => Object::Boolean(build_float_une(builder, left, right, "")),
+        Infix::Power => unreachable!("** is built by build_power"),
     }
 }
 
@@ -43,13 +87,45 @@ pub fn calculate_infix_boolean(
         Infix::Eq => Object::Boolean(build_int_eq(builder, left, right, "")),
         Infix::NotEq => Object::Boolean(build_int_ne(builder, left, right, "")),
         _ => Object::Error(format!(
-            "{} cannot be calculate for boolean. row: {}",
-            infix, location.row
+            "{} cannot be calculate for boolean. row: {}, column: {}",
+            infix, location.row, location.column
         )),
     }
 }
 
 pub fn resolve_left_integer(
+    builder: *mut LLVMBuilder,
+    infix: Infix,
+    integer_type: IntegerType,
+    left: *mut LLVMValue,
+    right_object: Object,
+    location: Location,
+) -> Object {
+    match right_object {
+        Object::Integer(right_type, right) => {
+            if right_type != integer_type {
+                return Object::Error(format!(
+                    "right value should be {}, but actually {}. row: {}, column: {}",
+                    LLVMExpressionType::Integer(integer_type),
+                    LLVMExpressionType::Integer(right_type),
+                    location.row,
+                    location.column,
+                ));
+            }
+            calculate_infix_integer(builder, infix, integer_type, left, right, location)
+        }
+        Object::Argument(_, func, index) => {
+            let right = get_param(func, index);
+            calculate_infix_integer(builder, infix, integer_type, left, right, location)
+        }
+        _ => Object::Error(format!(
+            "right value should be integer, but actually {}. row: {}, column: {}",
+            right_object, location.row, location.column,
+        )),
+    }
+}
+
+pub fn resolve_left_float(
     builder: *mut LLVMBuilder,
     infix: Infix,
     left: *mut LLVMValue,
@@ -57,14 +133,14 @@ pub fn resolve_left_integer(
     location: Location,
 ) -> Object {
     match right_object {
-        Object::Integer(right) => calculate_infix_integer(builder, infix, left, right, location),
+        Object::Float(right) => calculate_infix_float(builder, infix, left, right, location),
         Object::Argument(_, func, index) => {
             let right = get_param(func, index);
-            calculate_infix_integer(builder, infix, left, right, location)
+            calculate_infix_float(builder, infix, left, right, location)
         }
         _ => Object::Error(format!(
-            "right value should be integer, but actually {}. row: {}",
-            right_object, location.row,
+            "right value should be float, but actually {}. row: {}, column: {}",
+            right_object, location.row, location.column,
         )),
     }
 }
@@ -83,8 +159,8 @@ pub fn resolve_left_boolean(
             calculate_infix_boolean(builder, infix, left, right, location)
         }
         _ => Object::Error(format!(
-            "right value should be boolean, but actually {}. row: {}",
-            right_object, location.row,
+            "right value should be boolean, but actually {}. row: {}, column: {}",
+            right_object, location.row, location.column,
         )),
     }
 }
@@ -98,42 +174,74 @@ pub fn resolve_left_argument(
     location: Location,
 ) -> Object {
     match right_object {
-        Object::Integer(right) => calculate_infix_integer(builder, infix, left, right, location),
+        Object::Integer(integer_type, right) => {
+            calculate_infix_integer(builder, infix, integer_type, left, right, location)
+        }
+        Object::Float(right) => calculate_infix_float(builder, infix, left, right, location),
         Object::Boolean(right) => calculate_infix_boolean(builder, infix, left, right, location),
         Object::Argument(_, func, index) => {
             let right = get_param(func, index);
             match wrap_llvm_value(expression_type_left.clone(), right) {
-                Object::Integer(_) => {
-                    calculate_infix_integer(builder, infix, left, right, location)
+                Object::Integer(integer_type, _) => {
+                    calculate_infix_integer(builder, infix, integer_type, left, right, location)
                 }
+                Object::Float(_) => calculate_infix_float(builder, infix, left, right, location),
                 Object::Boolean(_) => {
                     calculate_infix_boolean(builder, infix, left, right, location)
                 }
                 _ => Object::Error(format!(
-                    "right cannot be analyzed, but actually {:?}. row: {}", // TODO
-                    expression_type_left, location.row,
+                    "right cannot be analyzed, but actually {:?}. row: {}, column: {}", // TODO
+                    expression_type_left, location.row, location.column,
                 )),
             }
         }
         _ => Object::Error(format!(
-            "right value should be boolean, but actually {}. row: {}",
-            right_object, location.row,
+            "right value should be boolean, but actually {}. row: {}, column: {}",
+            right_object, location.row, location.column,
+        )),
+    }
+}
+
+pub fn calculate_infix_string(
+    lc: &mut LLVMCreator,
+    infix: Infix,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+    location: Location,
+) -> Object {
+    if let Infix::Plus = infix {
+        return Object::String(build_string_concat(lc, left, right));
+    }
+
+    let builder = lc.builder;
+    let compared = build_string_compare(lc, left, right);
+    let zero = const_int(int32_type(), 0);
+    match infix {
+        Infix::Lt => Object::Boolean(build_int_slt(builder, compared, zero, "")),
+        Infix::Lte => Object::Boolean(build_int_sle(builder, compared, zero, "")),
+        Infix::Gt => Object::Boolean(build_int_sgt(builder, compared, zero, "")),
+        Infix::Gte => Object::Boolean(build_int_sge(builder, compared, zero, "")),
+        Infix::Eq => Object::Boolean(build_int_eq(builder, compared, zero, "")),
+        Infix::NotEq => Object::Boolean(build_int_ne(builder, compared, zero, "")),
+        _ => Object::Error(format!(
+            "{} cannot be used for string. row: {}, column: {}",
+            infix, location.row, location.column
         )),
     }
 }
 
-// TODO
 pub fn resolve_left_string(
-    _infix: Infix,
+    lc: &mut LLVMCreator,
+    infix: Infix,
     left: *mut LLVMValue,
     right_object: Object,
     location: Location,
 ) -> Object {
     match right_object {
-        Object::String(_, length) => Object::String(left, length), // TODO
+        Object::String(right) => calculate_infix_string(lc, infix, left, right, location),
         _ => Object::Error(format!(
-            "right value should be string, but actually {}. row: {}",
-            right_object, location.row,
+            "right value should be string, but actually {}. row: {}, column: {}",
+            right_object, location.row, location.column,
         )),
     }
 }
@@ -145,18 +253,19 @@ pub fn resolve_left_failed(
     location: Location,
 ) -> Object {
     let right_type_str = match right_object {
-        Object::Integer(_) => "integer",
-        Object::String(_, _) => "string",
+        Object::Integer(_, _) => "integer",
+        Object::Float(_) => "float",
+        Object::String(_) => "string",
         Object::Boolean(_) => "boolean",
         _ => {
             return Object::Error(format!(
-                "{} {} {} cannot be culculated. row: {}",
-                left_object, infix, right_object, location.row,
+                "{} {} {} cannot be culculated. row: {}, column: {}",
+                left_object, infix, right_object, location.row, location.column,
             ));
         }
     };
     Object::Error(format!(
-        "left value should be {}, but actually {}. row: {}",
-        right_type_str, left_object, location.row
+        "left value should be {}, but actually {}. row: {}, column: {}",
+        right_type_str, left_object, location.row, location.column
     ))
 }
@@ -1,5 +1,8 @@
+pub mod cast;
 pub mod environment;
 pub mod eval;
+pub mod format;
+pub mod header;
 pub mod infix;
 pub mod object;
 pub mod prefix;
@@ -6,45 +6,116 @@ use llvm_sys::*;
 
 #[derive(Debug, Clone)]
 pub enum Object {
-    Integer(*mut LLVMValue),
+    Integer(IntegerType, *mut LLVMValue),
+    Float(*mut LLVMValue),
     Boolean(*mut LLVMValue),
-    String(*mut LLVMValue, u32),
+    String(*mut LLVMValue),
     Array(LLVMExpressionType, *mut LLVMValue, u32),
     Function(Function),
     Null,
     Error(String),
     BuildIn(BuildIn),
     Argument(LLVMExpressionType, *mut LLVMValue, u32),
+    Extern(Extern),
 }
 
 #[derive(Debug, Clone)]
 pub enum BuildIn {
     Printf,
+    Print,
+    Println,
+    ReadLine,
+    ReadInt,
+    ReadOk,
+    Open,
+    ReadAll,
+    Write,
+    Close,
+    Exists,
+    Argc,
+    Argv,
+    Getenv,
+    Exit,
+    Assert,
+    Panic,
+    Abs,
+    Min,
+    Max,
+    Pow,
+    Sqrt,
+    Seed,
+    Rand,
+    RandRange,
+    ClockMs,
     Length,
+    Len,
+    ToFloat,
+    ToInt,
+}
+
+// a C function declared by `extern fn`
+#[derive(Debug, Clone)]
+pub struct Extern {
+    pub name: String,
+    pub llvm_value: *mut LLVMValue,
+    pub parameter_types: Vec<LLVMExpressionType>,
+    pub variadic: bool,
+    pub return_type: LLVMExpressionType,
 }
 
 #[derive(Debug, Clone)]
 pub struct Function {
     pub llvm_value: *mut LLVMValue,
     pub llvm_block: *mut LLVMBasicBlock,
+    pub parameter_types: Vec<LLVMExpressionType>,
     pub return_type: LLVMExpressionType,
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Object::Integer(_) => write!(f, "Integer"),        // TODO
-            Object::Boolean(_) => write!(f, "Boolean"),        // TODO
-            Object::String(_, _) => write!(f, "{}", "String"), // TODO
+            Object::Integer(_, _) => write!(f, "Integer"),  // TODO
+            Object::Float(_) => write!(f, "Float"),         // TODO
+            Object::Boolean(_) => write!(f, "Boolean"),     // TODO
+            Object::String(_) => write!(f, "{}", "String"), // TODO
             Object::Array(child_type, _, _) => write!(f, "{}", child_type), // TODO
             Object::Function(_) => write!(f, "{}", "TODO"),
             Object::Null => write!(f, "Null"),
             Object::Error(string) => write!(f, "{}", string),
             Object::BuildIn(build_in) => match build_in {
                 BuildIn::Printf => write!(f, "printf"),
+                BuildIn::Print => write!(f, "print"),
+                BuildIn::Println => write!(f, "println"),
+                BuildIn::ReadLine => write!(f, "read_line"),
+                BuildIn::ReadInt => write!(f, "read_int"),
+                BuildIn::ReadOk => write!(f, "read_ok"),
+                BuildIn::Open => write!(f, "open"),
+                BuildIn::ReadAll => write!(f, "read_all"),
+                BuildIn::Write => write!(f, "write"),
+                BuildIn::Close => write!(f, "close"),
+                BuildIn::Exists => write!(f, "exists"),
+                BuildIn::Argc => write!(f, "argc"),
+                BuildIn::Argv => write!(f, "argv"),
+                BuildIn::Getenv => write!(f, "getenv"),
+                BuildIn::Exit => write!(f, "exit"),
+                BuildIn::Assert => write!(f, "assert"),
+                BuildIn::Panic => write!(f, "panic"),
+                BuildIn::Abs => write!(f, "abs"),
+                BuildIn::Min => write!(f, "min"),
+                BuildIn::Max => write!(f, "max"),
+                BuildIn::Pow => write!(f, "pow"),
+                BuildIn::Sqrt => write!(f, "sqrt"),
+                BuildIn::Seed => write!(f, "seed"),
+                BuildIn::Rand => write!(f, "rand"),
+                BuildIn::RandRange => write!(f, "rand_range"),
+                BuildIn::ClockMs => write!(f, "clock_ms"),
                 BuildIn::Length => write!(f, "length"),
+                BuildIn::Len => write!(f, "len"),
+                BuildIn::ToFloat => write!(f, "to_float"),
+                BuildIn::ToInt => write!(f, "to_int"),
             },
             Object::Argument(_, _, _) => write!(f, "TODO"),
+            Object::Extern(extern_function) => write!(f, "{}", extern_function.name),
         }
     }
 }
@@ -5,8 +5,10 @@ use parser::prefix::*;
 
 use evaluate_ir::object::*;
 
+use ir::arithmetic::*;
 use ir::condition::*;
 use ir::const_value::*;
+use ir::converter::*;
 use ir::llvm_type::*;
 use ir::operate::*;
 
@@ -24,8 +26,8 @@ pub fn calculate_prefix_boolean(
             }
         }
         _ => Object::Error(format!(
-            "{} cannot be use for prefix. row: {}",
-            prefix, location.row
+            "{} cannot be use for prefix. row: {}, column: {}",
+            prefix, location.row, location.column
         )),
     }
 }
@@ -33,16 +35,33 @@ pub fn calculate_prefix_boolean(
 pub fn calculate_prefix_integer(
     builder: *mut LLVMBuilder,
     prefix: Prefix,
+    integer_type: IntegerType,
     value: *mut LLVMValue,
 ) -> Object {
     match prefix {
-        Prefix::Minus => Object::Integer(const_neg(value)),
-        Prefix::Plus => Object::Integer(value),
+        Prefix::Minus => Object::Integer(integer_type, neg_variable(builder, value, "")),
+        Prefix::Plus => Object::Integer(integer_type, value),
         Prefix::Bang => Object::Boolean(build_int_ult(
             builder,
-            const_int(int32_type(), 0),
+            const_int(convert_integer_type(integer_type), 0),
             value,
             "",
         )),
     }
 }
+
+pub fn calculate_prefix_float(
+    builder: *mut LLVMBuilder,
+    prefix: Prefix,
+    value: *mut LLVMValue,
+    location: Location,
+) -> Object {
+    match prefix {
+        Prefix::Minus => Object::Float(neg_float_variable(builder, value, "")),
+        Prefix::Plus => Object::Float(value),
+        _ => Object::Error(format!(
+            "{} cannot be use for float. row: {}, column: {}",
+            prefix, location.row, location.column
+        )),
+    }
+}
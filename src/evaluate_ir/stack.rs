@@ -1,7 +1,10 @@
 use llvm_sys::*;
 
+use parser::expressions::*;
+
 pub struct FunctionStack {
     pub stack: Vec<*mut LLVMValue>,
+    pub return_types: Vec<LLVMExpressionType>,
     pub main_func: *mut LLVMValue,
 }
 
@@ -9,15 +12,18 @@ impl FunctionStack {
     pub fn new(main_func: *mut LLVMValue) -> Self {
         FunctionStack {
             stack: Vec::new(),
+            return_types: Vec::new(),
             main_func: main_func,
         }
     }
 
-    pub fn push(&mut self, function: *mut LLVMValue) {
+    pub fn push(&mut self, function: *mut LLVMValue, return_type: LLVMExpressionType) {
         self.stack.push(function);
+        self.return_types.push(return_type);
     }
 
     pub fn pop(&mut self) -> *mut LLVMValue {
+        let _ = self.return_types.pop();
         if let Some(function) = self.stack.pop() {
             function
         } else {
@@ -32,4 +38,12 @@ impl FunctionStack {
             self.main_func
         }
     }
+
+    pub fn last_return_type(&self) -> LLVMExpressionType {
+        if let Some(return_type) = self.return_types.last() {
+            return_type.clone()
+        } else {
+            LLVMExpressionType::Integer(IntegerType::I32)
+        }
+    }
 }
@@ -61,6 +61,94 @@ pub fn divide_variable(
     unsafe { LLVMBuildUDiv(builder, var_a, var_b, c_string!(name).as_ptr()) }
 }
 
+#[allow(dead_code)]
+pub fn signed_rem_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildSRem(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn signed_divide_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildSDiv(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn neg_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildNeg(builder, var_a, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn add_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFAdd(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn sub_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFSub(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn multiple_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFMul(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn rem_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFRem(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn divide_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFDiv(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn neg_float_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFNeg(builder, var_a, c_string!(name).as_ptr()) }
+}
+
 #[allow(dead_code)]
 fn setup_llvm() -> LLVMCreator {
     let mut lc = LLVMCreator::new("test_module");
@@ -80,6 +168,46 @@ fn int_arithmetic_assert(actual: *mut LLVMValue, expect: *mut LLVMValue) {
     }
 }
 
+#[allow(dead_code)]
+pub fn xor_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildXor(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn and_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildAnd(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn or_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildOr(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn logical_shift_right_variable(
+    builder: *mut LLVMBuilder,
+    var_a: *mut LLVMValue,
+    var_b: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildLShr(builder, var_a, var_b, c_string!(name).as_ptr()) }
+}
+
 #[test]
 fn add() {
     unsafe {
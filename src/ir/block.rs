@@ -11,3 +11,8 @@ pub fn append_basic_block_in_context(
 ) -> *mut LLVMBasicBlock {
     unsafe { LLVMAppendBasicBlockInContext(context, function, c_string!(function_name).as_ptr()) }
 }
+
+#[allow(dead_code)]
+pub fn get_block_function(block: *mut LLVMBasicBlock) -> *mut LLVMValue {
+    unsafe { LLVMGetBasicBlockParent(block) }
+}
@@ -1,19 +1,154 @@
+use llvm_sys::core::*;
 use llvm_sys::*;
 
 use ir::function::*;
 use ir::llvm_type::*;
+use ir::scope::*;
+
+// size_t and long are declared as i64. create_machine refuses a program which uses them for
+// a target other than 64 bit linux, where both have 64 bits
 
 #[allow(dead_code)]
 pub fn create_printf(module: *mut LLVMModule) -> *mut LLVMValue {
     let mut printf_args_type_list = vec![pointer_type()];
-    let printf_type = function_type_var_arg(pointer_type(), &mut printf_args_type_list);
+    let printf_type = function_type_var_arg(int32_type(), &mut printf_args_type_list);
 
     add_function(module, printf_type, "printf")
 }
 
+#[allow(dead_code)]
+pub fn create_snprintf(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut snprintf_args_type_list = vec![pointer_type(), int64_type(), pointer_type()];
+    let snprintf_type = function_type_var_arg(int32_type(), &mut snprintf_args_type_list);
+
+    add_function(module, snprintf_type, "snprintf")
+}
+
 #[allow(dead_code)]
 pub fn create_strcmp(module: *mut LLVMModule) -> *mut LLVMValue {
     let mut strcmp_args_type_list = vec![pointer_type(), pointer_type()];
-    let strcmp_type = function_type_var_arg(int32_type(), &mut strcmp_args_type_list);
+    let strcmp_type = function_type(int32_type(), &mut strcmp_args_type_list);
     return add_function(module, strcmp_type, "strcmp");
 }
+
+#[allow(dead_code)]
+pub fn create_memcmp(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut memcmp_args_type_list = vec![pointer_type(), pointer_type(), int64_type()];
+    let memcmp_type = function_type(int32_type(), &mut memcmp_args_type_list);
+    add_function(module, memcmp_type, "memcmp")
+}
+
+#[allow(dead_code)]
+pub fn create_malloc(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut malloc_args_type_list = vec![int64_type()];
+    let malloc_type = function_type(pointer_type(), &mut malloc_args_type_list);
+    add_function(module, malloc_type, "malloc")
+}
+
+#[allow(dead_code)]
+pub fn create_memcpy(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut memcpy_args_type_list = vec![pointer_type(), pointer_type(), int64_type()];
+    let memcpy_type = function_type(pointer_type(), &mut memcpy_args_type_list);
+    add_function(module, memcpy_type, "memcpy")
+}
+
+#[allow(dead_code)]
+pub fn create_strlen(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut strlen_args_type_list = vec![pointer_type()];
+    let strlen_type = function_type(int64_type(), &mut strlen_args_type_list);
+    add_function(module, strlen_type, "strlen")
+}
+
+#[allow(dead_code)]
+pub fn create_realloc(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut realloc_args_type_list = vec![pointer_type(), int64_type()];
+    let realloc_type = function_type(pointer_type(), &mut realloc_args_type_list);
+    add_function(module, realloc_type, "realloc")
+}
+
+#[allow(dead_code)]
+pub fn create_fgetc(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut fgetc_args_type_list = vec![pointer_type()];
+    let fgetc_type = function_type(int32_type(), &mut fgetc_args_type_list);
+    add_function(module, fgetc_type, "fgetc")
+}
+
+#[allow(dead_code)]
+pub fn create_strtoll(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut strtoll_args_type_list = vec![
+        pointer_type(),
+        unsafe { LLVMPointerType(pointer_type(), 0) },
+        int32_type(),
+    ];
+    let strtoll_type = function_type(int64_type(), &mut strtoll_args_type_list);
+    add_function(module, strtoll_type, "strtoll")
+}
+
+// `FILE *stdin` of the C library
+#[allow(dead_code)]
+pub fn create_stdin(module: *mut LLVMModule) -> *mut LLVMValue {
+    add_global(module, pointer_type(), "stdin")
+}
+
+#[allow(dead_code)]
+pub fn create_fopen(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut fopen_args_type_list = vec![pointer_type(), pointer_type()];
+    let fopen_type = function_type(pointer_type(), &mut fopen_args_type_list);
+    add_function(module, fopen_type, "fopen")
+}
+
+#[allow(dead_code)]
+pub fn create_fclose(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut fclose_args_type_list = vec![pointer_type()];
+    let fclose_type = function_type(int32_type(), &mut fclose_args_type_list);
+    add_function(module, fclose_type, "fclose")
+}
+
+#[allow(dead_code)]
+pub fn create_fwrite(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut fwrite_args_type_list =
+        vec![pointer_type(), int64_type(), int64_type(), pointer_type()];
+    let fwrite_type = function_type(int64_type(), &mut fwrite_args_type_list);
+    add_function(module, fwrite_type, "fwrite")
+}
+
+#[allow(dead_code)]
+pub fn create_access(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut access_args_type_list = vec![pointer_type(), int32_type()];
+    let access_type = function_type(int32_type(), &mut access_args_type_list);
+    add_function(module, access_type, "access")
+}
+
+#[allow(dead_code)]
+pub fn create_getenv(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut getenv_args_type_list = vec![pointer_type()];
+    let getenv_type = function_type(pointer_type(), &mut getenv_args_type_list);
+    add_function(module, getenv_type, "getenv")
+}
+
+#[allow(dead_code)]
+pub fn create_exit(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut exit_args_type_list = vec![int32_type()];
+    let exit_type = function_type(void_type(), &mut exit_args_type_list);
+    add_function(module, exit_type, "exit")
+}
+
+#[allow(dead_code)]
+pub fn create_fputs(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut fputs_args_type_list = vec![pointer_type(), pointer_type()];
+    let fputs_type = function_type(int32_type(), &mut fputs_args_type_list);
+    add_function(module, fputs_type, "fputs")
+}
+
+// `FILE *stderr` of the C library
+#[allow(dead_code)]
+pub fn create_stderr(module: *mut LLVMModule) -> *mut LLVMValue {
+    add_global(module, pointer_type(), "stderr")
+}
+
+#[allow(dead_code)]
+pub fn create_clock_gettime(module: *mut LLVMModule) -> *mut LLVMValue {
+    let mut clock_gettime_args_type_list = vec![int32_type(), pointer_type()];
+    let clock_gettime_type = function_type(int32_type(), &mut clock_gettime_args_type_list);
+    add_function(module, clock_gettime_type, "clock_gettime")
+}
@@ -0,0 +1,131 @@
+use llvm_sys::*;
+
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+use ir::string::*;
+
+// exit status of a program stopped by a failed runtime check
+pub const CHECK_FAILURE_STATUS: u64 = 101;
+// exit status of a failed assert or a panic
+pub const PANIC_STATUS: u64 = 102;
+
+// `void runtime_abort(string message, i32 status)` prints the message to stderr and exits
+fn get_abort_function(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(function) = lc.get_runtime("runtime_abort") {
+        return function;
+    }
+
+    let function_type = function_type(void_type(), &mut [string_type(), int32_type()]);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function("runtime_abort", function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+
+    build_position_at_end(lc.builder, entry_block);
+    let message = get_string_pointer(lc.builder, get_param(function, 0));
+    let stderr = build_load(lc.builder, lc.built_ins["stderr"], "");
+    call_function(lc.builder, lc.built_ins["fputs"], vec![message, stderr], "");
+    call_function(
+        lc.builder,
+        lc.built_ins["exit"],
+        vec![get_param(function, 1)],
+        "",
+    );
+    build_unreachable(lc.builder);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+#[allow(dead_code)]
+pub fn build_abort(lc: &mut LLVMCreator, message: *mut LLVMValue, status: *mut LLVMValue) {
+    let abort = get_abort_function(lc);
+    call_function(lc.builder, abort, vec![message, status], "");
+}
+
+// aborts with a message and the status unless `is_ok`. the message is a printf format of
+// `arguments` and `max_length` is an upper bound of its formatted length
+#[allow(dead_code)]
+pub fn build_check(
+    lc: &mut LLVMCreator,
+    is_ok: *mut LLVMValue,
+    status: u64,
+    format: &str,
+    arguments: Vec<*mut LLVMValue>,
+    max_length: *mut LLVMValue,
+) {
+    let function = get_block_function(get_insert_block(lc.builder));
+    let failure_block = append_basic_block_in_context(lc.context, function, "");
+    let ok_block = append_basic_block_in_context(lc.context, function, "");
+    build_cond_br(lc.builder, is_ok, ok_block, failure_block);
+
+    build_position_at_end(lc.builder, failure_block);
+    let message = build_string_format(lc, format, arguments, max_length);
+    build_abort(lc, message, const_int(int32_type(), status));
+    build_unreachable(lc.builder);
+
+    build_position_at_end(lc.builder, ok_block);
+}
+
+// the wrapped result of `llvm.{s,u}{operation}.with.overflow` and whether it overflowed
+fn build_with_overflow(
+    lc: &mut LLVMCreator,
+    operation: &str,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> (*mut LLVMValue, *mut LLVMValue) {
+    let llvm_type = type_of(left);
+    let name = format!(
+        "llvm.{}{}.with.overflow.i{}",
+        if signed { "s" } else { "u" },
+        operation,
+        get_int_type_width(llvm_type)
+    );
+    let mut intrinsic = get_named_function(lc.module, &name);
+    if intrinsic.is_null() {
+        let return_type = struct_type(&mut [llvm_type, int1_type()]);
+        let function_type = function_type(return_type, &mut [llvm_type, llvm_type]);
+        intrinsic = add_function(lc.module, function_type, &name);
+    }
+
+    let result = call_function(lc.builder, intrinsic, vec![left, right], "");
+    (
+        build_extract_value(lc.builder, result, 0, ""),
+        build_extract_value(lc.builder, result, 1, ""),
+    )
+}
+
+#[allow(dead_code)]
+pub fn build_overflow_add(
+    lc: &mut LLVMCreator,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> (*mut LLVMValue, *mut LLVMValue) {
+    build_with_overflow(lc, "add", signed, left, right)
+}
+
+#[allow(dead_code)]
+pub fn build_overflow_sub(
+    lc: &mut LLVMCreator,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> (*mut LLVMValue, *mut LLVMValue) {
+    build_with_overflow(lc, "sub", signed, left, right)
+}
+
+#[allow(dead_code)]
+pub fn build_overflow_multiply(
+    lc: &mut LLVMCreator,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> (*mut LLVMValue, *mut LLVMValue) {
+    build_with_overflow(lc, "mul", signed, left, right)
+}
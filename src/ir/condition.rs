@@ -4,6 +4,7 @@ use llvm_sys::core::*;
 use llvm_sys::*;
 
 use llvm_sys::LLVMIntPredicate;
+use llvm_sys::LLVMRealPredicate;
 
 use ir::block::*;
 use ir::const_value::*;
@@ -30,6 +31,25 @@ pub fn build_cond_br(
     };
 }
 
+#[allow(dead_code)]
+pub fn build_select(
+    builder: *mut LLVMBuilder,
+    condition: *mut LLVMValue,
+    then_value: *mut LLVMValue,
+    else_value: *mut LLVMValue,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe {
+        LLVMBuildSelect(
+            builder,
+            condition,
+            then_value,
+            else_value,
+            c_string!(name).as_ptr(),
+        )
+    }
+}
+
 #[allow(dead_code)]
 pub fn build_br(builder: *mut LLVMBuilder, block: *mut LLVMBasicBlock) {
     unsafe {
@@ -70,6 +90,36 @@ create_build_i_cmp!(build_int_sge, LLVMIntPredicate::LLVMIntSGE);
 create_build_i_cmp!(build_int_slt, LLVMIntPredicate::LLVMIntSLT);
 create_build_i_cmp!(build_int_sle, LLVMIntPredicate::LLVMIntSLE);
 
+macro_rules! create_build_f_cmp {
+    ($name:ident, $condition:expr) => {
+        #[allow(dead_code)]
+        pub fn $name(
+            builder: *mut LLVMBuilder,
+            left_val: *mut LLVMValue,
+            right_val: *mut LLVMValue,
+            name: &str,
+        ) -> *mut LLVMValue {
+            unsafe {
+                LLVMBuildFCmp(
+                    builder,
+                    $condition,
+                    left_val,
+                    right_val,
+                    c_string!(name).as_ptr(),
+                )
+            }
+        }
+    };
+}
+
+create_build_f_cmp!(build_float_oeq, LLVMRealPredicate::LLVMRealOEQ);
+create_build_f_cmp!(build_float_one, LLVMRealPredicate::LLVMRealONE);
+create_build_f_cmp!(build_float_une, LLVMRealPredicate::LLVMRealUNE);
+create_build_f_cmp!(build_float_ogt, LLVMRealPredicate::LLVMRealOGT);
+create_build_f_cmp!(build_float_oge, LLVMRealPredicate::LLVMRealOGE);
+create_build_f_cmp!(build_float_olt, LLVMRealPredicate::LLVMRealOLT);
+create_build_f_cmp!(build_float_ole, LLVMRealPredicate::LLVMRealOLE);
+
 #[allow(dead_code)]
 pub fn create_if_else_test(
     lc: &mut LLVMCreator,
@@ -11,6 +11,16 @@ pub fn const_int(llvm_type: *mut LLVMType, value: u64) -> *mut LLVMValue {
     unsafe { LLVMConstInt(llvm_type, value, 0) }
 }
 
+#[allow(dead_code)]
+pub fn const_real(llvm_type: *mut LLVMType, value: f64) -> *mut LLVMValue {
+    unsafe { LLVMConstReal(llvm_type, value) }
+}
+
+#[allow(dead_code)]
+pub fn const_null(llvm_type: *mut LLVMType) -> *mut LLVMValue {
+    unsafe { LLVMConstNull(llvm_type) }
+}
+
 #[allow(dead_code)]
 pub fn const_neg(value: *mut LLVMValue) -> *mut LLVMValue {
     unsafe { LLVMConstNeg(value) }
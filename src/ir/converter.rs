@@ -6,8 +6,9 @@ use parser::expressions::*;
 
 pub fn get_llvm_type_from_object(object: &mut Object) -> *mut LLVMType {
     match *object {
-        Object::Integer(_) => int32_type(),
-        Object::String(_, length) => array_type(int8_type(), length),
+        Object::Integer(integer_type, _) => convert_integer_type(integer_type),
+        Object::Float(_) => double_type(),
+        Object::String(_) => string_type(),
         Object::Boolean(_) => int1_type(),
         Object::Function(_) => int1_type(), // need to fix
         Object::Array(ref child_type, _, length) => {
@@ -18,11 +19,21 @@ pub fn get_llvm_type_from_object(object: &mut Object) -> *mut LLVMType {
     }
 }
 
+pub fn convert_integer_type(integer_type: IntegerType) -> *mut LLVMType {
+    match integer_type.bit_width() {
+        8 => int8_type(),
+        16 => int16_type(),
+        64 => int64_type(),
+        _ => int32_type(),
+    }
+}
+
 pub fn convert_llvm_type(expression_type: LLVMExpressionType) -> *mut LLVMType {
     match expression_type {
-        LLVMExpressionType::Integer => int32_type(),
+        LLVMExpressionType::Integer(integer_type) => convert_integer_type(integer_type),
+        LLVMExpressionType::Float => double_type(),
         LLVMExpressionType::Boolean => int1_type(),
-        LLVMExpressionType::String(length) => array_type(int8_type(), length),
+        LLVMExpressionType::String => string_type(),
         LLVMExpressionType::Null => void_type(),
         LLVMExpressionType::Function => int32_type(), // need to fix
         LLVMExpressionType::Array(child_type, length) => {
@@ -33,10 +44,25 @@ pub fn convert_llvm_type(expression_type: LLVMExpressionType) -> *mut LLVMType {
     }
 }
 
+pub fn get_expression_type_from_object(object: &Object) -> LLVMExpressionType {
+    match *object {
+        Object::Integer(integer_type, _) => LLVMExpressionType::Integer(integer_type),
+        Object::Float(_) => LLVMExpressionType::Float,
+        Object::Boolean(_) => LLVMExpressionType::Boolean,
+        Object::String(_) => LLVMExpressionType::String,
+        Object::Array(ref child_type, _, length) => {
+            LLVMExpressionType::Array(Box::new(child_type.clone()), length)
+        }
+        Object::Argument(ref expression_type, _, _) => expression_type.clone(),
+        _ => LLVMExpressionType::Null,
+    }
+}
+
 pub fn unwrap_object(object: &mut Object) -> *mut LLVMValue {
     match *object {
-        Object::Integer(llvm_value) => llvm_value,
-        Object::String(llvm_value, _) => llvm_value,
+        Object::Integer(_, llvm_value) => llvm_value,
+        Object::Float(llvm_value) => llvm_value,
+        Object::String(llvm_value) => llvm_value,
         Object::Boolean(llvm_value) => llvm_value,
         Object::Function(ref func) => func.llvm_value,
         Object::Array(_, llvm_value, _) => llvm_value,
@@ -44,10 +70,23 @@ pub fn unwrap_object(object: &mut Object) -> *mut LLVMValue {
     }
 }
 
+// the type of a C function parameter or return value. strings are passed as `char*`
+pub fn convert_c_type(expression_type: LLVMExpressionType) -> Option<*mut LLVMType> {
+    match expression_type {
+        LLVMExpressionType::String => Some(pointer_type()),
+        LLVMExpressionType::Integer(_)
+        | LLVMExpressionType::Float
+        | LLVMExpressionType::Boolean
+        | LLVMExpressionType::Null => Some(convert_llvm_type(expression_type)),
+        _ => None,
+    }
+}
+
 pub fn wrap_llvm_value(expression_type: LLVMExpressionType, llvm_value: *mut LLVMValue) -> Object {
     match expression_type {
-        LLVMExpressionType::Integer => Object::Integer(llvm_value),
-        LLVMExpressionType::String(length) => Object::String(llvm_value, length),
+        LLVMExpressionType::Integer(integer_type) => Object::Integer(integer_type, llvm_value),
+        LLVMExpressionType::Float => Object::Float(llvm_value),
+        LLVMExpressionType::String => Object::String(llvm_value),
         LLVMExpressionType::Boolean => Object::Boolean(llvm_value),
         LLVMExpressionType::Array(child_type, array_length) => {
             Object::Array(*child_type, llvm_value, array_length)
@@ -58,8 +97,9 @@ pub fn wrap_llvm_value(expression_type: LLVMExpressionType, llvm_value: *mut LLV
 
 pub fn rewrap_llvm_value_ref(object: Object, llvm_value_ref: *mut LLVMValue) -> Object {
     match object {
-        Object::Integer(_) => Object::Integer(llvm_value_ref),
-        Object::String(_, length) => Object::String(llvm_value_ref, length),
+        Object::Integer(integer_type, _) => Object::Integer(integer_type, llvm_value_ref),
+        Object::Float(_) => Object::Float(llvm_value_ref),
+        Object::String(_) => Object::String(llvm_value_ref),
         Object::Boolean(_) => Object::Boolean(llvm_value_ref),
         Object::Array(llvm_child_type, _, array_length) => {
             Object::Array(llvm_child_type, llvm_value_ref, array_length)
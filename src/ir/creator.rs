@@ -2,16 +2,27 @@ use std::collections::HashMap;
 use std::ffi::CString;
 use std::path::Path;
 
+use llvm_sys::bit_writer::*;
 use llvm_sys::core::*;
+use llvm_sys::LLVMLinkage::*;
 use llvm_sys::*;
 
 use ir::built_in::*;
+use ir::function::*;
+use ir::scope::*;
+use ir::target::*;
+
+// the prefix of functions and globals the compiler defines for itself.
+// `.` cannot be written in an identifier, so they never collide with the names of a program
+pub const RUNTIME_PREFIX: &'static str = "__mr_rt.";
 
 pub struct LLVMCreator {
     pub builder: *mut LLVMBuilder,
     pub context: *mut LLVMContext,
     pub module: *mut LLVMModule,
     pub built_ins: HashMap<&'static str, *mut LLVMValue>,
+    // runtime helpers and globals by their name without the prefix, defined on first use
+    pub runtime: HashMap<String, *mut LLVMValue>,
 }
 
 impl LLVMCreator {
@@ -25,6 +36,7 @@ impl LLVMCreator {
                 module: LLVMModuleCreateWithName(mod_name.as_ptr()),
                 context: context,
                 built_ins: HashMap::new(),
+                runtime: HashMap::new(),
             };
             lc.setup_builtin();
             lc
@@ -34,7 +46,63 @@ impl LLVMCreator {
     #[allow(dead_code)]
     pub fn setup_builtin(&mut self) {
         self.built_ins.insert("printf", create_printf(self.module));
+        self.built_ins
+            .insert("snprintf", create_snprintf(self.module));
         self.built_ins.insert("strcmp", create_strcmp(self.module));
+        self.built_ins.insert("memcmp", create_memcmp(self.module));
+        self.built_ins.insert("strlen", create_strlen(self.module));
+        self.built_ins.insert("malloc", create_malloc(self.module));
+        self.built_ins.insert("memcpy", create_memcpy(self.module));
+        self.built_ins
+            .insert("realloc", create_realloc(self.module));
+        self.built_ins.insert("fgetc", create_fgetc(self.module));
+        self.built_ins
+            .insert("strtoll", create_strtoll(self.module));
+        self.built_ins.insert("stdin", create_stdin(self.module));
+        self.built_ins.insert("fopen", create_fopen(self.module));
+        self.built_ins.insert("fclose", create_fclose(self.module));
+        self.built_ins.insert("fwrite", create_fwrite(self.module));
+        self.built_ins.insert("access", create_access(self.module));
+        self.built_ins.insert("getenv", create_getenv(self.module));
+        self.built_ins.insert("exit", create_exit(self.module));
+        self.built_ins.insert("fputs", create_fputs(self.module));
+        self.built_ins.insert("stderr", create_stderr(self.module));
+        self.built_ins
+            .insert("clock_gettime", create_clock_gettime(self.module));
+    }
+
+    #[allow(dead_code)]
+    pub fn get_runtime(&self, name: &str) -> Option<*mut LLVMValue> {
+        self.runtime.get(name).cloned()
+    }
+
+    // the body is built by the caller. helpers are internal, so a module defines its own
+    #[allow(dead_code)]
+    pub fn add_runtime_function(
+        &mut self,
+        name: &str,
+        function_type: *mut LLVMType,
+    ) -> *mut LLVMValue {
+        let symbol = format!("{}{}", RUNTIME_PREFIX, name);
+        let function = add_function(self.module, function_type, &symbol);
+        set_linkage(function, LLVMInternalLinkage);
+        self.runtime.insert(name.to_string(), function);
+        function
+    }
+
+    #[allow(dead_code)]
+    pub fn add_runtime_global(
+        &mut self,
+        name: &str,
+        llvm_type: *mut LLVMType,
+        initializer: *mut LLVMValue,
+    ) -> *mut LLVMValue {
+        let symbol = format!("{}{}", RUNTIME_PREFIX, name);
+        let global = add_global(self.module, llvm_type, &symbol);
+        set_linkage(global, LLVMInternalLinkage);
+        set_initializer(global, initializer);
+        self.runtime.insert(name.to_string(), global);
+        global
     }
 
     #[allow(dead_code)]
@@ -43,17 +111,31 @@ impl LLVMCreator {
     }
 
     #[allow(dead_code)]
-    pub fn emit_file<P: AsRef<Path>>(&self, path: P) {
+    pub fn emit_file<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
         let path = path
             .as_ref()
             .to_str()
-            .expect("Did not find a valid Unicode path string");
+            .ok_or_else(|| "Did not find a valid Unicode path string".to_string())?;
         let mut error: *mut i8 = 0 as *mut i8;
-        let buf: *mut *mut i8 = &mut error;
-        let result = unsafe { LLVMPrintModuleToFile(self.module, path.as_ptr() as *const _, buf) };
-        if result > 0 {
-            println!("{}", string_from_raw!(error));
+        let result =
+            unsafe { LLVMPrintModuleToFile(self.module, c_string!(path).as_ptr(), &mut error) };
+        if result != 0 {
+            return Err(format!("failed to write {}: {}", path, take_message(error)));
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn emit_bitcode<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path
+            .as_ref()
+            .to_str()
+            .ok_or_else(|| "Did not find a valid Unicode path string".to_string())?;
+        let result = unsafe { LLVMWriteBitcodeToFile(self.module, c_string!(path).as_ptr()) };
+        if result != 0 {
+            return Err(format!("failed to write bitcode to {}", path));
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,263 @@
+use llvm_sys::*;
+
+use ir::arithmetic::*;
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::input::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+use ir::string::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+// `F_OK` of access(2)
+const FILE_EXISTS: u64 = 0;
+const FAILURE: u64 = -1i32 as u64;
+const FILE_SLOTS: u64 = 256;
+
+// files are kept in programs as an i64 handle into a table of open files.
+// 0 is a file which failed to open. a handle is `serial * FILE_SLOTS + slot` with a serial
+// which grows on every open, so a closed or made up handle never names an open file
+#[allow(dead_code)]
+pub fn build_open(
+    lc: &mut LLVMCreator,
+    path: *mut LLVMValue,
+    mode: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let open = get_open_function(lc);
+    call_function(lc.builder, open, vec![path, mode], "")
+}
+
+// `FILE* file_table[FILE_SLOTS]` and `i64 file_handles[FILE_SLOTS]`. a free slot has the handle 0
+fn get_file_table(lc: &mut LLVMCreator) -> (*mut LLVMValue, *mut LLVMValue) {
+    if let (Some(files), Some(handles)) =
+        (lc.get_runtime("file_table"), lc.get_runtime("file_handles"))
+    {
+        return (files, handles);
+    }
+    let files_type = array_type(pointer_type(), FILE_SLOTS as u32);
+    let handles_type = array_type(int64_type(), FILE_SLOTS as u32);
+    let files = lc.add_runtime_global("file_table", files_type, const_null(files_type));
+    let handles = lc.add_runtime_global("file_handles", handles_type, const_null(handles_type));
+    (files, handles)
+}
+
+// pointers to the `FILE*` and the handle of the slot of `handle`
+fn build_file_slot(
+    lc: &mut LLVMCreator,
+    handle: *mut LLVMValue,
+) -> (*mut LLVMValue, *mut LLVMValue) {
+    let (files, handles) = get_file_table(lc);
+    let slot = rem_variable(lc.builder, handle, const_int(int64_type(), FILE_SLOTS), "");
+    let zero = const_int(int32_type(), 0);
+    (
+        build_gep(lc.builder, files, vec![zero, slot], ""),
+        build_gep(lc.builder, handles, vec![zero, slot], ""),
+    )
+}
+
+// `i64 file_open(i8* path, i8* mode)` opens the file in the first free slot.
+// the file is closed again and 0 is returned if every slot is used
+fn get_open_function(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(function) = lc.get_runtime("file_open") {
+        return function;
+    }
+
+    let (files, handles) = get_file_table(lc);
+    let serial = lc.add_runtime_global("file_serial", int64_type(), const_int(int64_type(), 0));
+    let function_type = function_type(int64_type(), &mut [pointer_type(), pointer_type()]);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function("file_open", function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let failure_block = append_basic_block_in_context(lc.context, function, "");
+    let loop_block = append_basic_block_in_context(lc.context, function, "");
+    let check_block = append_basic_block_in_context(lc.context, function, "");
+    let next_block = append_basic_block_in_context(lc.context, function, "");
+    let full_block = append_basic_block_in_context(lc.context, function, "");
+    let found_block = append_basic_block_in_context(lc.context, function, "");
+
+    build_position_at_end(lc.builder, entry_block);
+    let index = build_alloca(lc.builder, int64_type(), "");
+    build_store(lc.builder, const_int(int64_type(), 0), index);
+    let path = get_param(function, 0);
+    let mode = get_param(function, 1);
+    let file = call_function(lc.builder, lc.built_ins["fopen"], vec![path, mode], "");
+    let is_null = build_int_eq(lc.builder, file, const_null(pointer_type()), "");
+    build_cond_br(lc.builder, is_null, failure_block, loop_block);
+
+    build_position_at_end(lc.builder, failure_block);
+    build_ret(lc.builder, const_int(int64_type(), 0));
+
+    build_position_at_end(lc.builder, loop_block);
+    let current = build_load(lc.builder, index, "");
+    let slots = const_int(int64_type(), FILE_SLOTS);
+    let is_end = build_int_eq(lc.builder, current, slots, "");
+    build_cond_br(lc.builder, is_end, full_block, check_block);
+
+    build_position_at_end(lc.builder, check_block);
+    let zero = const_int(int32_type(), 0);
+    let slot_handle = build_gep(lc.builder, handles, vec![zero, current], "");
+    let slot_handle = build_load(lc.builder, slot_handle, "");
+    let is_free = build_int_eq(lc.builder, slot_handle, const_int(int64_type(), 0), "");
+    build_cond_br(lc.builder, is_free, found_block, next_block);
+
+    build_position_at_end(lc.builder, next_block);
+    let next = add_variable(lc.builder, current, const_int(int64_type(), 1), "");
+    build_store(lc.builder, next, index);
+    build_br(lc.builder, loop_block);
+
+    build_position_at_end(lc.builder, full_block);
+    call_function(lc.builder, lc.built_ins["fclose"], vec![file], "");
+    build_ret(lc.builder, const_int(int64_type(), 0));
+
+    build_position_at_end(lc.builder, found_block);
+    let next_serial = build_load(lc.builder, serial, "");
+    let next_serial = add_variable(lc.builder, next_serial, const_int(int64_type(), 1), "");
+    build_store(lc.builder, next_serial, serial);
+    let handle = multiple_variable(lc.builder, next_serial, slots, "");
+    let handle = add_variable(lc.builder, handle, current, "");
+    let slot_file = build_gep(lc.builder, files, vec![zero, current], "");
+    let slot_handle = build_gep(lc.builder, handles, vec![zero, current], "");
+    build_store(lc.builder, file, slot_file);
+    build_store(lc.builder, handle, slot_handle);
+    build_ret(lc.builder, handle);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+#[allow(dead_code)]
+pub fn build_exists(lc: &mut LLVMCreator, path: *mut LLVMValue) -> *mut LLVMValue {
+    let result = call_function(
+        lc.builder,
+        lc.built_ins["access"],
+        vec![path, const_int(int32_type(), FILE_EXISTS)],
+        "",
+    );
+    build_int_eq(lc.builder, result, const_int(int32_type(), 0), "")
+}
+
+// defines `name(i64 file, parameters...)` which returns `failure` for a handle of no open file.
+// otherwise `build_body` gets the function and the `FILE*` and builds the result
+fn get_file_function<F>(
+    lc: &mut LLVMCreator,
+    name: &str,
+    return_type: *mut LLVMType,
+    mut parameter_types: Vec<*mut LLVMType>,
+    failure: *mut LLVMValue,
+    build_body: F,
+) -> *mut LLVMValue
+where
+    F: FnOnce(&mut LLVMCreator, *mut LLVMValue, *mut LLVMValue) -> *mut LLVMValue,
+{
+    if let Some(function) = lc.get_runtime(name) {
+        return function;
+    }
+
+    parameter_types.insert(0, int64_type());
+    let function_type = function_type(return_type, &mut parameter_types);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function(name, function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let failure_block = append_basic_block_in_context(lc.context, function, "");
+    let body_block = append_basic_block_in_context(lc.context, function, "");
+    let handle = get_param(function, 0);
+
+    build_position_at_end(lc.builder, entry_block);
+    let (slot_file, slot_handle) = build_file_slot(lc, handle);
+    let slot_handle = build_load(lc.builder, slot_handle, "");
+    let is_open = build_int_eq(lc.builder, slot_handle, handle, "");
+    let is_used = build_int_ne(lc.builder, slot_handle, const_int(int64_type(), 0), "");
+    let is_open = and_variable(lc.builder, is_open, is_used, "");
+    build_cond_br(lc.builder, is_open, body_block, failure_block);
+
+    build_position_at_end(lc.builder, failure_block);
+    build_ret(lc.builder, failure);
+
+    build_position_at_end(lc.builder, body_block);
+    let file = build_load(lc.builder, slot_file, "");
+    let result = build_body(lc, function, file);
+    build_ret(lc.builder, result);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+// the read status is false for a file which failed to open
+#[allow(dead_code)]
+pub fn build_read_all(lc: &mut LLVMCreator, handle: *mut LLVMValue) -> *mut LLVMValue {
+    let status = get_read_status(lc);
+    let read_until = get_read_until_function(lc);
+    let empty = codegen_string_value(lc, "");
+    let read_all = get_file_function(
+        lc,
+        "file_read_all",
+        string_type(),
+        vec![],
+        empty,
+        |lc, _, file| {
+            let end_of_file = const_int(int32_type(), EOF);
+            let contents = call_function(lc.builder, read_until, vec![file, end_of_file], "");
+            // an empty file is read successfully too
+            build_store(lc.builder, const_int(int1_type(), 1), status);
+            contents
+        },
+    );
+
+    // the failure branch of the helper only returns, so the status is cleared here
+    build_store(lc.builder, const_int(int1_type(), 0), status);
+    call_function(lc.builder, read_all, vec![handle], "")
+}
+
+// returns the number of bytes written, or -1 for a file which failed to open
+#[allow(dead_code)]
+pub fn build_write(
+    lc: &mut LLVMCreator,
+    handle: *mut LLVMValue,
+    string_value: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let write = get_file_function(
+        lc,
+        "file_write",
+        int32_type(),
+        vec![string_type()],
+        const_int(int32_type(), FAILURE),
+        |lc, function, file| {
+            let string_value = get_param(function, 1);
+            let pointer = get_string_pointer(lc.builder, string_value);
+            let length = get_string_length(lc.builder, string_value);
+            let length = build_cast(lc.builder, LLVMOpcode::LLVMZExt, length, int64_type(), "");
+            let written = call_function(
+                lc.builder,
+                lc.built_ins["fwrite"],
+                vec![pointer, const_int(int64_type(), 1), length, file],
+                "",
+            );
+            build_cast(lc.builder, LLVMOpcode::LLVMTrunc, written, int32_type(), "")
+        },
+    );
+    call_function(lc.builder, write, vec![handle, string_value], "")
+}
+
+// returns 0, or -1 if the file is not open or failed to be flushed. the handle is invalid afterwards
+#[allow(dead_code)]
+pub fn build_close(lc: &mut LLVMCreator, handle: *mut LLVMValue) -> *mut LLVMValue {
+    let close = get_file_function(
+        lc,
+        "file_close",
+        int32_type(),
+        vec![],
+        const_int(int32_type(), FAILURE),
+        |lc, function, file| {
+            let (slot_file, slot_handle) = build_file_slot(lc, get_param(function, 0));
+            build_store(lc.builder, const_null(pointer_type()), slot_file);
+            build_store(lc.builder, const_int(int64_type(), 0), slot_handle);
+            call_function(lc.builder, lc.built_ins["fclose"], vec![file], "")
+        },
+    );
+    call_function(lc.builder, close, vec![handle], "")
+}
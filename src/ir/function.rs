@@ -5,6 +5,7 @@ use llvm_sys::*;
 
 use ir::creator::*;
 use ir::operate::*;
+use ir::scope::*;
 
 #[allow(unused_imports)]
 use ir::block::*;
@@ -33,6 +34,12 @@ pub fn add_function(
     unsafe { LLVMAddFunction(target_module, c_string!(name).as_ptr(), function_type) }
 }
 
+// the function type, not the pointer type, of a function
+#[allow(dead_code)]
+pub fn get_function_type(function: *mut LLVMValue) -> *mut LLVMType {
+    unsafe { LLVMGetElementType(LLVMTypeOf(function)) }
+}
+
 #[allow(dead_code)]
 pub fn call_function(
     builder: *mut LLVMBuilder,
@@ -56,17 +63,26 @@ pub fn get_param(target_func: *mut LLVMValue, arg_index: u32) -> *mut LLVMValue
     unsafe { LLVMGetParam(target_func, arg_index) }
 }
 
+// functions are anonymous and internal until `export fn` names them
 #[allow(dead_code)]
 pub fn create_function(
     lc: &mut LLVMCreator,
     fn_type: *mut LLVMType,
 ) -> (*mut LLVMValue, *mut LLVMBasicBlock) {
     let function = add_function(lc.module, fn_type, "");
+    set_linkage(function, LLVMLinkage::LLVMInternalLinkage);
     let block = append_basic_block(function, "entry");
     build_position_at_end(lc.builder, block);
     (function, block)
 }
 
+// gives the function an external symbol
+#[allow(dead_code)]
+pub fn export_function(function: *mut LLVMValue, name: &str) {
+    unsafe { LLVMSetValueName2(function, c_string!(name).as_ptr(), name.len()) }
+    set_linkage(function, LLVMLinkage::LLVMExternalLinkage);
+}
+
 #[allow(dead_code)]
 pub fn get_named_function(module: *mut LLVMModule, name: &str) -> *mut LLVMValue {
     unsafe { LLVMGetNamedFunction(module, c_string!(name).as_ptr()) }
@@ -0,0 +1,297 @@
+use llvm_sys::*;
+
+use ir::arithmetic::*;
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+use ir::string::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+pub const EOF: u64 = -1i32 as u64;
+const NEWLINE: u64 = 10;
+const INITIAL_LINE_CAPACITY: u64 = 16;
+
+// whether the last read succeeded
+pub fn get_read_status(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(status) = lc.get_runtime("read_ok") {
+        return status;
+    }
+    lc.add_runtime_global("read_ok", int1_type(), const_int(int1_type(), 0))
+}
+
+#[allow(dead_code)]
+pub fn build_read_status(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    let status = get_read_status(lc);
+    build_load(lc.builder, status, "")
+}
+
+#[allow(dead_code)]
+pub fn build_stdin(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    build_load(lc.builder, lc.built_ins["stdin"], "")
+}
+
+// `string read_until(FILE*, i32 delimiter)` reads up to the delimiter, which is dropped.
+// the status is false only if the file is already at the end
+pub fn get_read_until_function(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(function) = lc.get_runtime("read_until") {
+        return function;
+    }
+
+    let function_type = function_type(string_type(), &mut [pointer_type(), int32_type()]);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function("read_until", function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let loop_block = append_basic_block_in_context(lc.context, function, "");
+    let character_block = append_basic_block_in_context(lc.context, function, "");
+    let append_block = append_basic_block_in_context(lc.context, function, "");
+    let grow_block = append_basic_block_in_context(lc.context, function, "");
+    let store_block = append_basic_block_in_context(lc.context, function, "");
+    let end_of_file_block = append_basic_block_in_context(lc.context, function, "");
+    let delimiter_block = append_basic_block_in_context(lc.context, function, "");
+    let finish_block = append_basic_block_in_context(lc.context, function, "");
+    let file = get_param(function, 0);
+    let delimiter = get_param(function, 1);
+    let status = get_read_status(lc);
+
+    build_position_at_end(lc.builder, entry_block);
+    let buffer = build_alloca(lc.builder, pointer_type(), "");
+    let capacity = build_alloca(lc.builder, int64_type(), "");
+    let length = build_alloca(lc.builder, int64_type(), "");
+    let initial_capacity = const_int(int64_type(), INITIAL_LINE_CAPACITY);
+    let memory = call_function(
+        lc.builder,
+        lc.built_ins["malloc"],
+        vec![initial_capacity],
+        "",
+    );
+    build_store(lc.builder, memory, buffer);
+    build_store(lc.builder, initial_capacity, capacity);
+    build_store(lc.builder, const_int(int64_type(), 0), length);
+    build_br(lc.builder, loop_block);
+
+    build_position_at_end(lc.builder, loop_block);
+    let character = call_function(lc.builder, lc.built_ins["fgetc"], vec![file], "");
+    let is_end_of_file = build_int_eq(lc.builder, character, const_int(int32_type(), EOF), "");
+    build_cond_br(
+        lc.builder,
+        is_end_of_file,
+        end_of_file_block,
+        character_block,
+    );
+
+    build_position_at_end(lc.builder, character_block);
+    let is_delimiter = build_int_eq(lc.builder, character, delimiter, "");
+    build_cond_br(lc.builder, is_delimiter, delimiter_block, append_block);
+
+    // one byte is kept for the terminating null
+    build_position_at_end(lc.builder, append_block);
+    let current_length = build_load(lc.builder, length, "");
+    let current_capacity = build_load(lc.builder, capacity, "");
+    let next_length = add_variable(lc.builder, current_length, const_int(int64_type(), 1), "");
+    let is_full = build_int_eq(lc.builder, next_length, current_capacity, "");
+    build_cond_br(lc.builder, is_full, grow_block, store_block);
+
+    build_position_at_end(lc.builder, grow_block);
+    let next_capacity = add_variable(lc.builder, current_capacity, current_capacity, "");
+    let memory = build_load(lc.builder, buffer, "");
+    let memory = call_function(
+        lc.builder,
+        lc.built_ins["realloc"],
+        vec![memory, next_capacity],
+        "",
+    );
+    build_store(lc.builder, memory, buffer);
+    build_store(lc.builder, next_capacity, capacity);
+    build_br(lc.builder, store_block);
+
+    build_position_at_end(lc.builder, store_block);
+    let memory = build_load(lc.builder, buffer, "");
+    let end = build_gep(lc.builder, memory, vec![current_length], "");
+    let byte = build_cast(
+        lc.builder,
+        LLVMOpcode::LLVMTrunc,
+        character,
+        int8_type(),
+        "",
+    );
+    build_store(lc.builder, byte, end);
+    build_store(lc.builder, next_length, length);
+    build_br(lc.builder, loop_block);
+
+    // the text after the last delimiter still counts
+    build_position_at_end(lc.builder, end_of_file_block);
+    let current_length = build_load(lc.builder, length, "");
+    let has_line = build_int_ne(lc.builder, current_length, const_int(int64_type(), 0), "");
+    build_store(lc.builder, has_line, status);
+    build_br(lc.builder, finish_block);
+
+    build_position_at_end(lc.builder, delimiter_block);
+    build_store(lc.builder, const_int(int1_type(), 1), status);
+    build_br(lc.builder, finish_block);
+
+    build_position_at_end(lc.builder, finish_block);
+    let memory = build_load(lc.builder, buffer, "");
+    let current_length = build_load(lc.builder, length, "");
+    let end = build_gep(lc.builder, memory, vec![current_length], "");
+    build_store(lc.builder, const_int(int8_type(), 0), end);
+    let current_length = build_cast(
+        lc.builder,
+        LLVMOpcode::LLVMTrunc,
+        current_length,
+        int32_type(),
+        "",
+    );
+    let string_value = build_string_value(lc, memory, current_length);
+    build_ret(lc.builder, string_value);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+// `i32 read_int(FILE*)` reads a line holding a decimal integer.
+// the status is false at the end of the file or if the line is not an integer which fits in i32
+fn get_read_int_function(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(function) = lc.get_runtime("read_int") {
+        return function;
+    }
+
+    let read_until = get_read_until_function(lc);
+    let function_type = function_type(int32_type(), &mut [pointer_type()]);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function("read_int", function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let file = get_param(function, 0);
+    let status = get_read_status(lc);
+
+    build_position_at_end(lc.builder, entry_block);
+    let end = build_alloca(lc.builder, pointer_type(), "");
+    let newline = const_int(int32_type(), NEWLINE);
+    let line = call_function(lc.builder, read_until, vec![file, newline], "");
+    let start = get_string_pointer(lc.builder, line);
+    let value = call_function(
+        lc.builder,
+        lc.built_ins["strtoll"],
+        vec![start, end, const_int(int32_type(), 10)],
+        "",
+    );
+    let end = build_load(lc.builder, end, "");
+    let has_digits = build_int_ne(lc.builder, end, start, "");
+    let rest = build_load(lc.builder, end, "");
+    let is_whole_line = build_int_eq(lc.builder, rest, const_int(int8_type(), 0), "");
+    let is_integer = build_select(lc.builder, has_digits, is_whole_line, has_digits, "");
+    // strtoll saturates on overflow, so this also rejects values beyond i64
+    let minimum = const_int(int64_type(), i32::min_value() as u64);
+    let maximum = const_int(int64_type(), i32::max_value() as u64);
+    let above_minimum = build_int_sge(lc.builder, value, minimum, "");
+    let below_maximum = build_int_sle(lc.builder, value, maximum, "");
+    let is_in_range = and_variable(lc.builder, above_minimum, below_maximum, "");
+    let is_integer = and_variable(lc.builder, is_integer, is_in_range, "");
+    let has_line = build_load(lc.builder, status, "");
+    let is_ok = build_select(lc.builder, has_line, is_integer, has_line, "");
+    build_store(lc.builder, is_ok, status);
+
+    let value = build_cast(lc.builder, LLVMOpcode::LLVMTrunc, value, int32_type(), "");
+    let value = build_select(lc.builder, is_ok, value, const_int(int32_type(), 0), "");
+    build_ret(lc.builder, value);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+#[allow(dead_code)]
+pub fn build_read_line(lc: &mut LLVMCreator, file: *mut LLVMValue) -> *mut LLVMValue {
+    let read_until = get_read_until_function(lc);
+    let newline = const_int(int32_type(), NEWLINE);
+    call_function(lc.builder, read_until, vec![file, newline], "")
+}
+
+#[allow(dead_code)]
+pub fn build_read_int(lc: &mut LLVMCreator, file: *mut LLVMValue) -> *mut LLVMValue {
+    let read_int = get_read_int_function(lc);
+    call_function(lc.builder, read_int, vec![file], "")
+}
+
+#[test]
+fn read_lines_and_integers() {
+    let mut lc = LLVMCreator::new("test_module");
+    let main = setup_main(&mut lc);
+    let input = "12\nabc\n-3\n3000000000\nx";
+    let mut fmemopen_args_type_list = vec![pointer_type(), int64_type(), pointer_type()];
+    let fmemopen_type = function_type(pointer_type(), &mut fmemopen_args_type_list);
+    let fmemopen = add_function(lc.module, fmemopen_type, "fmemopen");
+    let fmemopen_args = vec![
+        codegen_string(&mut lc, input, ".str"),
+        const_int(int64_type(), input.len() as u64),
+        codegen_string(&mut lc, "r", ".str"),
+    ];
+    let file = call_function(lc.builder, fmemopen, fmemopen_args, "");
+
+    let first = build_read_int(&mut lc, file);
+    let first_ok = build_read_status(&mut lc);
+    let line = build_read_line(&mut lc, file);
+    let line_length = get_string_length(lc.builder, line);
+    let last = build_read_int(&mut lc, file);
+    let out_of_range = build_read_int(&mut lc, file);
+    let out_of_range_ok = build_read_status(&mut lc);
+    build_read_int(&mut lc, file);
+    let not_integer_ok = build_read_status(&mut lc);
+    build_read_line(&mut lc, file);
+    let end_ok = build_read_status(&mut lc);
+
+    // packs every result into one number
+    let digits = vec![
+        (first, 1),
+        (line_length, 100),
+        (
+            build_cast(lc.builder, LLVMOpcode::LLVMZExt, first_ok, int32_type(), ""),
+            1000,
+        ),
+        (
+            build_cast(
+                lc.builder,
+                LLVMOpcode::LLVMZExt,
+                not_integer_ok,
+                int32_type(),
+                "",
+            ),
+            10000,
+        ),
+        (
+            build_cast(lc.builder, LLVMOpcode::LLVMZExt, end_ok, int32_type(), ""),
+            100000,
+        ),
+        (
+            add_variable(lc.builder, last, const_int(int32_type(), 3), ""),
+            1000000,
+        ),
+        (
+            add_variable(
+                lc.builder,
+                out_of_range,
+                build_cast(
+                    lc.builder,
+                    LLVMOpcode::LLVMZExt,
+                    out_of_range_ok,
+                    int32_type(),
+                    "",
+                ),
+                "",
+            ),
+            10000000,
+        ),
+    ];
+    let mut result = const_int(int32_type(), 0);
+    for (value, scale) in digits {
+        let value = multiple_variable(lc.builder, value, const_int(int32_type(), scale), "");
+        result = add_variable(lc.builder, result, value, "");
+    }
+    build_ret(lc.builder, result);
+
+    assert_eq!(execute_test_ir_function(lc.module, main), 1312);
+}
@@ -8,16 +8,41 @@ pub fn int32_type() -> *mut LLVMType {
     unsafe { LLVMInt32Type() }
 }
 
+#[allow(dead_code)]
+pub fn int64_type() -> *mut LLVMType {
+    unsafe { LLVMInt64Type() }
+}
+
+#[allow(dead_code)]
+pub fn int16_type() -> *mut LLVMType {
+    unsafe { LLVMInt16Type() }
+}
+
 #[allow(dead_code)]
 pub fn int8_type() -> *mut LLVMType {
     unsafe { LLVMInt8Type() }
 }
 
+#[allow(dead_code)]
+pub fn double_type() -> *mut LLVMType {
+    unsafe { LLVMDoubleType() }
+}
+
 #[allow(dead_code)]
 pub fn int1_type() -> *mut LLVMType {
     unsafe { LLVMInt1Type() }
 }
 
+#[allow(dead_code)]
+pub fn int_type(bit_width: u32) -> *mut LLVMType {
+    unsafe { LLVMIntType(bit_width) }
+}
+
+#[allow(dead_code)]
+pub fn get_int_type_width(llvm_type: *mut LLVMType) -> u32 {
+    unsafe { LLVMGetIntTypeWidth(llvm_type) }
+}
+
 #[allow(dead_code)]
 pub fn array_type(llvm_type: *mut LLVMType, length: u32) -> *mut LLVMType {
     unsafe { LLVMArrayType(llvm_type, length) }
@@ -53,7 +78,61 @@ pub fn cast_type(
     unsafe { LLVMBuildBitCast(builder, value, dest_type, c_string!(name).as_ptr()) }
 }
 
+#[allow(dead_code)]
+pub fn build_cast(
+    builder: *mut LLVMBuilder,
+    opcode: LLVMOpcode,
+    value: *mut LLVMValue,
+    dest_type: *mut LLVMType,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildCast(builder, opcode, value, dest_type, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn build_si_to_fp(
+    builder: *mut LLVMBuilder,
+    value: *mut LLVMValue,
+    dest_type: *mut LLVMType,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildSIToFP(builder, value, dest_type, c_string!(name).as_ptr()) }
+}
+
+#[allow(dead_code)]
+pub fn build_fp_to_si(
+    builder: *mut LLVMBuilder,
+    value: *mut LLVMValue,
+    dest_type: *mut LLVMType,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildFPToSI(builder, value, dest_type, c_string!(name).as_ptr()) }
+}
+
 #[allow(dead_code)]
 pub fn void_type() -> *mut LLVMType {
     unsafe { LLVMVoidType() }
 }
+
+#[allow(dead_code)]
+pub fn struct_type(elements: &mut [*mut LLVMType]) -> *mut LLVMType {
+    unsafe { LLVMStructType(elements.as_mut_ptr(), elements.len() as u32, 0) }
+}
+
+// { i8*, i32 }: a null terminated buffer and its length in bytes
+#[allow(dead_code)]
+pub fn string_struct_type() -> *mut LLVMType {
+    struct_type(&mut [pointer_type(), int32_type()])
+}
+
+// runtime strings are passed around as a pointer to an immutable string struct
+#[allow(dead_code)]
+pub fn string_type() -> *mut LLVMType {
+    unsafe { LLVMPointerType(string_struct_type(), 0) }
+}
+
+// i8**: argv of main
+#[allow(dead_code)]
+pub fn argv_type() -> *mut LLVMType {
+    unsafe { LLVMPointerType(pointer_type(), 0) }
+}
@@ -0,0 +1,350 @@
+use llvm_sys::*;
+
+use ir::arithmetic::*;
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+// declares an intrinsic on double, e.g. `llvm.sqrt.f64`, on its first use
+fn get_float_intrinsic(lc: &mut LLVMCreator, name: &str, parameter_count: usize) -> *mut LLVMValue {
+    let function = get_named_function(lc.module, name);
+    if !function.is_null() {
+        return function;
+    }
+
+    let mut parameter_types = vec![double_type(); parameter_count];
+    let function_type = function_type(double_type(), &mut parameter_types);
+    add_function(lc.module, function_type, name)
+}
+
+#[allow(dead_code)]
+pub fn build_int_abs(builder: *mut LLVMBuilder, value: *mut LLVMValue) -> *mut LLVMValue {
+    let zero = const_int(type_of(value), 0);
+    let is_negative = build_int_slt(builder, value, zero, "");
+    let negated = sub_variable(builder, zero, value, "");
+    build_select(builder, is_negative, negated, value, "")
+}
+
+#[allow(dead_code)]
+pub fn build_int_min(
+    builder: *mut LLVMBuilder,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let is_less = if signed {
+        build_int_slt(builder, left, right, "")
+    } else {
+        build_int_ult(builder, left, right, "")
+    };
+    build_select(builder, is_less, left, right, "")
+}
+
+#[allow(dead_code)]
+pub fn build_int_max(
+    builder: *mut LLVMBuilder,
+    signed: bool,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let is_greater = if signed {
+        build_int_sgt(builder, left, right, "")
+    } else {
+        build_int_ugt(builder, left, right, "")
+    };
+    build_select(builder, is_greater, left, right, "")
+}
+
+#[allow(dead_code)]
+pub fn build_float_abs(lc: &mut LLVMCreator, value: *mut LLVMValue) -> *mut LLVMValue {
+    let fabs = get_float_intrinsic(lc, "llvm.fabs.f64", 1);
+    call_function(lc.builder, fabs, vec![value], "")
+}
+
+// a nan operand gives the other one
+#[allow(dead_code)]
+pub fn build_float_min(
+    lc: &mut LLVMCreator,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let minnum = get_float_intrinsic(lc, "llvm.minnum.f64", 2);
+    call_function(lc.builder, minnum, vec![left, right], "")
+}
+
+#[allow(dead_code)]
+pub fn build_float_max(
+    lc: &mut LLVMCreator,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let maxnum = get_float_intrinsic(lc, "llvm.maxnum.f64", 2);
+    call_function(lc.builder, maxnum, vec![left, right], "")
+}
+
+#[allow(dead_code)]
+pub fn build_float_power(
+    lc: &mut LLVMCreator,
+    base: *mut LLVMValue,
+    exponent: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let pow = get_float_intrinsic(lc, "llvm.pow.f64", 2);
+    call_function(lc.builder, pow, vec![base, exponent], "")
+}
+
+#[allow(dead_code)]
+pub fn build_float_sqrt(lc: &mut LLVMCreator, value: *mut LLVMValue) -> *mut LLVMValue {
+    let sqrt = get_float_intrinsic(lc, "llvm.sqrt.f64", 1);
+    call_function(lc.builder, sqrt, vec![value], "")
+}
+
+// `int_pow.<type>(base, exponent)` multiplies by squaring and wraps on overflow.
+// a negative exponent gives 0 unless the base is 1 or -1
+fn get_int_power_function(
+    lc: &mut LLVMCreator,
+    type_name: &str,
+    llvm_type: *mut LLVMType,
+    signed: bool,
+) -> *mut LLVMValue {
+    let name = format!("int_pow.{}", type_name);
+    if let Some(function) = lc.get_runtime(&name) {
+        return function;
+    }
+
+    let function_type = function_type(llvm_type, &mut [llvm_type, llvm_type]);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function(&name, function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let fraction_block = append_basic_block_in_context(lc.context, function, "");
+    let start_block = append_basic_block_in_context(lc.context, function, "");
+    let loop_block = append_basic_block_in_context(lc.context, function, "");
+    let square_block = append_basic_block_in_context(lc.context, function, "");
+    let finish_block = append_basic_block_in_context(lc.context, function, "");
+    let base = get_param(function, 0);
+    let exponent = get_param(function, 1);
+    let zero = const_int(llvm_type, 0);
+    let one = const_int(llvm_type, 1);
+
+    build_position_at_end(lc.builder, entry_block);
+    let result = build_alloca(lc.builder, llvm_type, "");
+    let square = build_alloca(lc.builder, llvm_type, "");
+    let remaining = build_alloca(lc.builder, llvm_type, "");
+    let is_negative = if signed {
+        build_int_slt(lc.builder, exponent, zero, "")
+    } else {
+        const_int(int1_type(), 0)
+    };
+    let is_one = build_int_eq(lc.builder, base, one, "");
+    let is_minus_one = build_int_eq(lc.builder, base, const_int(llvm_type, u64::max_value()), "");
+    let is_unit = or_variable(lc.builder, is_one, is_minus_one, "");
+    let is_unit = if signed { is_unit } else { is_one };
+    let is_fraction = and_variable(
+        lc.builder,
+        is_negative,
+        build_int_eq(lc.builder, is_unit, const_int(int1_type(), 0), ""),
+        "",
+    );
+    build_cond_br(lc.builder, is_fraction, fraction_block, start_block);
+
+    build_position_at_end(lc.builder, fraction_block);
+    build_ret(lc.builder, zero);
+
+    // the powers of 1 and -1 only depend on the parity, so the magnitude of the exponent is used
+    build_position_at_end(lc.builder, start_block);
+    let negated = sub_variable(lc.builder, zero, exponent, "");
+    let magnitude = build_select(lc.builder, is_negative, negated, exponent, "");
+    build_store(lc.builder, one, result);
+    build_store(lc.builder, base, square);
+    build_store(lc.builder, magnitude, remaining);
+    build_br(lc.builder, loop_block);
+
+    build_position_at_end(lc.builder, loop_block);
+    let current_remaining = build_load(lc.builder, remaining, "");
+    let is_done = build_int_eq(lc.builder, current_remaining, zero, "");
+    build_cond_br(lc.builder, is_done, finish_block, square_block);
+
+    build_position_at_end(lc.builder, square_block);
+    let current_result = build_load(lc.builder, result, "");
+    let current_square = build_load(lc.builder, square, "");
+    let low_bit = and_variable(lc.builder, current_remaining, one, "");
+    let is_odd = build_int_ne(lc.builder, low_bit, zero, "");
+    let multiplied = multiple_variable(lc.builder, current_result, current_square, "");
+    let next_result = build_select(lc.builder, is_odd, multiplied, current_result, "");
+    let next_square = multiple_variable(lc.builder, current_square, current_square, "");
+    let next_remaining = logical_shift_right_variable(lc.builder, current_remaining, one, "");
+    build_store(lc.builder, next_result, result);
+    build_store(lc.builder, next_square, square);
+    build_store(lc.builder, next_remaining, remaining);
+    build_br(lc.builder, loop_block);
+
+    build_position_at_end(lc.builder, finish_block);
+    let final_result = build_load(lc.builder, result, "");
+    build_ret(lc.builder, final_result);
+
+    build_position_at_end(lc.builder, current_block);
+    function
+}
+
+#[allow(dead_code)]
+pub fn build_int_power(
+    lc: &mut LLVMCreator,
+    type_name: &str,
+    signed: bool,
+    base: *mut LLVMValue,
+    exponent: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let power = get_int_power_function(lc, type_name, type_of(base), signed);
+    call_function(lc.builder, power, vec![base, exponent], "")
+}
+
+// the floor of the square root, or 0 for a negative value.
+// the root through double is corrected by one in either direction for 64 bit values
+#[allow(dead_code)]
+pub fn build_int_sqrt(lc: &mut LLVMCreator, signed: bool, value: *mut LLVMValue) -> *mut LLVMValue {
+    let builder = lc.builder;
+    let llvm_type = type_of(value);
+    let zero = const_int(llvm_type, 0);
+    let one = const_int(llvm_type, 1);
+    let value = if signed {
+        let is_negative = build_int_slt(builder, value, zero, "");
+        build_select(builder, is_negative, zero, value, "")
+    } else {
+        value
+    };
+
+    let float_value = build_cast(builder, LLVMOpcode::LLVMUIToFP, value, double_type(), "");
+    let float_root = build_float_sqrt(lc, float_value);
+    let root = build_cast(builder, LLVMOpcode::LLVMFPToUI, float_root, llvm_type, "");
+
+    // the square of the largest root has to fit in the type
+    let half_width = get_int_type_width(llvm_type) / 2;
+    let max_root = const_int(llvm_type, (1 << half_width) - 1);
+    let root = build_int_min(builder, false, root, max_root);
+
+    let square = multiple_variable(builder, root, root, "");
+    let is_over = build_int_ugt(builder, square, value, "");
+    let lower = sub_variable(builder, root, one, "");
+    let root = build_select(builder, is_over, lower, root, "");
+
+    let upper = add_variable(builder, root, one, "");
+    let upper_square = multiple_variable(builder, upper, upper, "");
+    let fits = build_int_ule(builder, upper, max_root, "");
+    let is_under = build_int_ule(builder, upper_square, value, "");
+    let is_under = and_variable(builder, fits, is_under, "");
+    build_select(builder, is_under, upper, root, "")
+}
+
+// builds `build_value` in a main on i64 and returns it
+#[allow(dead_code)]
+fn execute_math_test<F>(build_value: F) -> u64
+where
+    F: FnOnce(&mut LLVMCreator) -> *mut LLVMValue,
+{
+    let mut lc = LLVMCreator::new("test_module");
+    let main = setup_main(&mut lc);
+    let value = build_value(&mut lc);
+    let value = build_cast(lc.builder, LLVMOpcode::LLVMTrunc, value, int32_type(), "");
+    build_ret(lc.builder, value);
+    execute_test_ir_function(lc.module, main)
+}
+
+// the value is loaded so that the calculation is not folded into a constant
+#[allow(dead_code)]
+fn load_value(
+    lc: &mut LLVMCreator,
+    llvm_type: *mut LLVMType,
+    value: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let pointer = build_alloca(lc.builder, llvm_type, "");
+    build_store(lc.builder, value, pointer);
+    build_load(lc.builder, pointer, "")
+}
+
+#[test]
+fn integer_math() {
+    let int = |lc: &mut LLVMCreator, value: i64| {
+        load_value(lc, int64_type(), const_int(int64_type(), value as u64))
+    };
+    let power = |base: i64, exponent: i64| {
+        execute_math_test(|lc| {
+            let base = int(lc, base);
+            let exponent = int(lc, exponent);
+            build_int_power(lc, "i64", true, base, exponent)
+        }) as u32 as i32
+    };
+    assert_eq!(power(3, 4), 81);
+    assert_eq!(power(-2, 3), -8);
+    assert_eq!(power(7, 0), 1);
+    assert_eq!(power(2, -1), 0);
+    assert_eq!(power(-1, -3), -1);
+
+    let sqrt = |value: i64| {
+        execute_math_test(|lc| {
+            let value = int(lc, value);
+            build_int_sqrt(lc, true, value)
+        }) as u32
+    };
+    assert_eq!(sqrt(0), 0);
+    assert_eq!(sqrt(15), 3);
+    assert_eq!(sqrt(16), 4);
+    assert_eq!(sqrt(-4), 0);
+    assert_eq!(sqrt(0x7fff_ffff_ffff_ffff), 3_037_000_499);
+
+    let abs = execute_math_test(|lc| {
+        let value = int(lc, -5);
+        build_int_abs(lc.builder, value)
+    });
+    assert_eq!(abs, 5);
+    let min = execute_math_test(|lc| {
+        let left = int(lc, -5);
+        let right = int(lc, 3);
+        build_int_min(lc.builder, true, left, right)
+    });
+    assert_eq!(min as u32 as i32, -5);
+    let max = execute_math_test(|lc| {
+        let left = int(lc, -5);
+        let right = int(lc, 3);
+        build_int_max(lc.builder, false, left, right)
+    });
+    assert_eq!(max as u32 as i32, -5);
+}
+
+#[test]
+fn float_math() {
+    // the results are truncated to integers
+    let float = |lc: &mut LLVMCreator, value: f64| {
+        load_value(lc, double_type(), const_real(double_type(), value))
+    };
+    let to_int = |lc: &mut LLVMCreator, value: *mut LLVMValue| {
+        build_fp_to_si(lc.builder, value, int64_type(), "")
+    };
+    let result = execute_math_test(|lc| {
+        let base = float(lc, 2.0);
+        let exponent = float(lc, 10.0);
+        let value = build_float_power(lc, base, exponent);
+        to_int(lc, value)
+    });
+    assert_eq!(result, 1024);
+    let result = execute_math_test(|lc| {
+        let value = float(lc, 2.25);
+        let value = build_float_sqrt(lc, value);
+        let value = multiple_float_variable(lc.builder, value, const_real(double_type(), 10.0), "");
+        to_int(lc, value)
+    });
+    assert_eq!(result, 15);
+    let result = execute_math_test(|lc| {
+        let value = float(lc, -7.5);
+        let value = build_float_abs(lc, value);
+        let other = float(lc, 3.5);
+        let value = build_float_max(lc, value, other);
+        to_int(lc, value)
+    });
+    assert_eq!(result, 7);
+}
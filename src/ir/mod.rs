@@ -1,15 +1,23 @@
 pub mod arithmetic;
 pub mod block;
 pub mod built_in;
+pub mod check;
 pub mod condition;
 pub mod const_value;
 pub mod converter;
 pub mod creator;
+pub mod file;
 pub mod function;
+pub mod input;
 pub mod llvm_struct;
 pub mod llvm_type;
+pub mod math;
 pub mod operate;
+pub mod optimize;
+pub mod process;
+pub mod random;
 pub mod scope;
 pub mod string;
+pub mod target;
 pub mod test_util;
 pub mod validate;
@@ -44,6 +44,11 @@ pub fn build_ret_void(builder: *mut LLVMBuilder) -> *mut LLVMValue {
     unsafe { LLVMBuildRetVoid(builder) }
 }
 
+#[allow(dead_code)]
+pub fn build_unreachable(builder: *mut LLVMBuilder) -> *mut LLVMValue {
+    unsafe { LLVMBuildUnreachable(builder) }
+}
+
 #[allow(dead_code)]
 pub fn run_function(
     engine: LLVMExecutionEngineRef,
@@ -66,6 +71,11 @@ pub fn build_position_at_end(builder: *mut LLVMBuilder, block: *mut LLVMBasicBlo
     };
 }
 
+#[allow(dead_code)]
+pub fn get_insert_block(builder: *mut LLVMBuilder) -> *mut LLVMBasicBlock {
+    unsafe { LLVMGetInsertBlock(builder) }
+}
+
 #[allow(dead_code)]
 pub fn build_gep(
     builder: *mut LLVMBuilder,
@@ -84,6 +94,16 @@ pub fn build_gep(
     }
 }
 
+#[allow(dead_code)]
+pub fn build_extract_value(
+    builder: *mut LLVMBuilder,
+    aggregate: *mut LLVMValue,
+    index: u32,
+    name: &str,
+) -> *mut LLVMValue {
+    unsafe { LLVMBuildExtractValue(builder, aggregate, index, c_string!(name).as_ptr()) }
+}
+
 #[allow(dead_code)]
 pub fn build_const_gep(llvm_const_value: *mut LLVMValue) -> *mut LLVMValue {
     let mut args = vec![const_int(int32_type(), 0), const_int(int32_type(), 0)];
@@ -0,0 +1,151 @@
+use llvm_sys::core::*;
+use llvm_sys::target_machine::LLVMCodeGenOptLevel;
+use llvm_sys::transforms::pass_manager_builder::*;
+use llvm_sys::*;
+
+#[allow(unused_imports)]
+use std::ffi::CStr;
+
+#[allow(unused_imports)]
+use ir::block::*;
+
+#[allow(unused_imports)]
+use ir::const_value::*;
+
+#[allow(unused_imports)]
+use ir::creator::*;
+
+#[allow(unused_imports)]
+use ir::function::*;
+
+#[allow(unused_imports)]
+use ir::llvm_type::*;
+
+#[allow(unused_imports)]
+use ir::operate::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum OptimizationLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    Os,
+}
+
+impl OptimizationLevel {
+    #[allow(dead_code)]
+    pub fn all() -> [OptimizationLevel; 5] {
+        [
+            OptimizationLevel::O0,
+            OptimizationLevel::O1,
+            OptimizationLevel::O2,
+            OptimizationLevel::O3,
+            OptimizationLevel::Os,
+        ]
+    }
+
+    // the value of `-O`
+    pub fn from_name(name: &str) -> Option<OptimizationLevel> {
+        match name {
+            "0" => Some(OptimizationLevel::O0),
+            "1" => Some(OptimizationLevel::O1),
+            "2" => Some(OptimizationLevel::O2),
+            "3" => Some(OptimizationLevel::O3),
+            "s" => Some(OptimizationLevel::Os),
+            _ => None,
+        }
+    }
+
+    fn speed_level(&self) -> u32 {
+        match self {
+            OptimizationLevel::O0 => 0,
+            OptimizationLevel::O1 => 1,
+            OptimizationLevel::O3 => 3,
+            OptimizationLevel::O2 | OptimizationLevel::Os => 2,
+        }
+    }
+
+    fn size_level(&self) -> u32 {
+        match self {
+            OptimizationLevel::Os => 1,
+            _ => 0,
+        }
+    }
+
+    // the thresholds clang uses for each level
+    fn inline_threshold(&self) -> u32 {
+        match self {
+            OptimizationLevel::O3 => 250,
+            OptimizationLevel::Os => 75,
+            _ => 225,
+        }
+    }
+
+    pub fn code_gen_level(&self) -> LLVMCodeGenOptLevel {
+        match self {
+            OptimizationLevel::O0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            OptimizationLevel::O1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            OptimizationLevel::O3 => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+            OptimizationLevel::O2 | OptimizationLevel::Os => {
+                LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault
+            }
+        }
+    }
+}
+
+// runs the standard pipeline of the level, such as mem2reg, instcombine, GVN and inlining
+#[allow(dead_code)]
+pub fn optimize_module(module: *mut LLVMModule, level: OptimizationLevel) {
+    if level == OptimizationLevel::O0 {
+        return;
+    }
+    unsafe {
+        let builder = LLVMPassManagerBuilderCreate();
+        LLVMPassManagerBuilderSetOptLevel(builder, level.speed_level());
+        LLVMPassManagerBuilderSetSizeLevel(builder, level.size_level());
+        LLVMPassManagerBuilderUseInlinerWithThreshold(builder, level.inline_threshold());
+
+        let function_passes = LLVMCreateFunctionPassManagerForModule(module);
+        LLVMPassManagerBuilderPopulateFunctionPassManager(builder, function_passes);
+        LLVMInitializeFunctionPassManager(function_passes);
+        let mut function = LLVMGetFirstFunction(module);
+        while function.is_null() == false {
+            LLVMRunFunctionPassManager(function_passes, function);
+            function = LLVMGetNextFunction(function);
+        }
+        LLVMFinalizeFunctionPassManager(function_passes);
+        LLVMDisposePassManager(function_passes);
+
+        let module_passes = LLVMCreatePassManager();
+        LLVMPassManagerBuilderPopulateModulePassManager(builder, module_passes);
+        LLVMRunPassManager(module_passes, module);
+        LLVMDisposePassManager(module_passes);
+
+        LLVMPassManagerBuilderDispose(builder);
+    }
+}
+
+#[test]
+fn promote_allocas() {
+    let mut lc = LLVMCreator::new("test_module");
+    let main = setup_main(&mut lc);
+    let variable = build_alloca(lc.builder, int32_type(), "variable");
+    build_store(lc.builder, const_int(int32_type(), 42), variable);
+    let value = build_load(lc.builder, variable, "");
+    build_ret(lc.builder, value);
+
+    optimize_module(lc.module, OptimizationLevel::O1);
+    let ir = unsafe {
+        let message = LLVMPrintModuleToString(lc.module);
+        let ir = CStr::from_ptr(message).to_string_lossy().into_owned();
+        LLVMDisposeMessage(message);
+        ir
+    };
+    assert!(ir.contains("alloca") == false, "{}", ir);
+    assert!(ir.contains("ret i32 42"), "{}", ir);
+    assert_eq!(execute_test_ir_function(lc.module, main), 42);
+}
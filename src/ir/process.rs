@@ -0,0 +1,83 @@
+use llvm_sys::*;
+
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+use ir::string::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+// argc and argv of main are kept in globals so that every function can reach them
+fn get_argument_global(
+    lc: &mut LLVMCreator,
+    name: &str,
+    llvm_type: *mut LLVMType,
+) -> *mut LLVMValue {
+    if let Some(global) = lc.get_runtime(name) {
+        return global;
+    }
+    lc.add_runtime_global(name, llvm_type, const_null(llvm_type))
+}
+
+#[allow(dead_code)]
+pub fn build_store_arguments(lc: &mut LLVMCreator, main_function: *mut LLVMValue) {
+    let argc = get_argument_global(lc, "argc", int32_type());
+    let argv = get_argument_global(lc, "argv", argv_type());
+    build_store(lc.builder, get_param(main_function, 0), argc);
+    build_store(lc.builder, get_param(main_function, 1), argv);
+}
+
+#[allow(dead_code)]
+pub fn build_argc(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    let argc = get_argument_global(lc, "argc", int32_type());
+    build_load(lc.builder, argc, "")
+}
+
+// an index out of range gives an empty string.
+// argv is null in a library, whose main never runs, so it is only read for an index in range
+#[allow(dead_code)]
+pub fn build_argv(lc: &mut LLVMCreator, index: *mut LLVMValue) -> *mut LLVMValue {
+    let argc = build_argc(lc);
+    let argv = get_argument_global(lc, "argv", argv_type());
+    let argv = build_load(lc.builder, argv, "");
+    let no_argument = get_argument_global(lc, "no_argument", pointer_type());
+
+    let is_not_negative = build_int_sge(lc.builder, index, const_int(int32_type(), 0), "");
+    let is_below_argc = build_int_slt(lc.builder, index, argc, "");
+    let is_in_range = build_select(
+        lc.builder,
+        is_not_negative,
+        is_below_argc,
+        is_not_negative,
+        "",
+    );
+    let index = build_select(
+        lc.builder,
+        is_in_range,
+        index,
+        const_int(int32_type(), 0),
+        "",
+    );
+    let argv = build_select(lc.builder, is_in_range, argv, no_argument, "");
+    let argument = build_gep(lc.builder, argv, vec![index], "");
+    let argument = build_load(lc.builder, argument, "");
+    let empty = codegen_string(lc, "", ".str");
+    let argument = build_select(lc.builder, is_in_range, argument, empty, "");
+    build_c_string_value(lc, argument)
+}
+
+// an unset variable gives an empty string
+#[allow(dead_code)]
+pub fn build_getenv(lc: &mut LLVMCreator, name: *mut LLVMValue) -> *mut LLVMValue {
+    let value = call_function(lc.builder, lc.built_ins["getenv"], vec![name], "");
+    build_c_string_value(lc, value)
+}
+
+#[allow(dead_code)]
+pub fn build_exit(lc: &mut LLVMCreator, code: *mut LLVMValue) {
+    call_function(lc.builder, lc.built_ins["exit"], vec![code], "");
+}
@@ -0,0 +1,185 @@
+use llvm_sys::*;
+
+use ir::arithmetic::*;
+use ir::block::*;
+use ir::condition::*;
+use ir::const_value::*;
+use ir::creator::*;
+use ir::function::*;
+use ir::llvm_type::*;
+use ir::operate::*;
+
+#[allow(unused_imports)]
+use ir::test_util::*;
+
+// `CLOCK_MONOTONIC` of clock_gettime(2) on linux, the only os create_machine accepts clock_ms for
+const CLOCK_MONOTONIC: u64 = 1;
+// the increment and the multipliers of splitmix64
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+const FIRST_MIX: u64 = 0xbf58_476d_1ce4_e5b9;
+const SECOND_MIX: u64 = 0x94d0_49bb_1331_11eb;
+
+// the state of the generator. a program which never calls seed starts from 0
+fn get_random_state(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(state) = lc.get_runtime("rand_state") {
+        return state;
+    }
+    lc.add_runtime_global("rand_state", int64_type(), const_int(int64_type(), 0))
+}
+
+// `i64 rand_next()` advances the state by splitmix64, so a seed always gives the same sequence
+fn get_random_function(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    if let Some(function) = lc.get_runtime("rand_next") {
+        return function;
+    }
+
+    let state = get_random_state(lc);
+    let function_type = function_type(int64_type(), &mut []);
+    let current_block = get_insert_block(lc.builder);
+    let function = lc.add_runtime_function("rand_next", function_type);
+    let entry_block = append_basic_block_in_context(lc.context, function, "");
+    let builder = lc.builder;
+
+    build_position_at_end(builder, entry_block);
+    let current_state = build_load(builder, state, "");
+    let next_state = add_variable(
+        builder,
+        current_state,
+        const_int(int64_type(), GOLDEN_GAMMA),
+        "",
+    );
+    build_store(builder, next_state, state);
+
+    let mut value = next_state;
+    for &(shift, multiplier) in [(30, FIRST_MIX), (27, SECOND_MIX)].iter() {
+        let shifted =
+            logical_shift_right_variable(builder, value, const_int(int64_type(), shift), "");
+        let mixed = xor_variable(builder, value, shifted, "");
+        value = multiple_variable(builder, mixed, const_int(int64_type(), multiplier), "");
+    }
+    let shifted = logical_shift_right_variable(builder, value, const_int(int64_type(), 31), "");
+    let value = xor_variable(builder, value, shifted, "");
+    build_ret(builder, value);
+
+    build_position_at_end(builder, current_block);
+    function
+}
+
+#[allow(dead_code)]
+pub fn build_seed(lc: &mut LLVMCreator, seed: *mut LLVMValue) {
+    let state = get_random_state(lc);
+    let seed = build_cast(lc.builder, LLVMOpcode::LLVMSExt, seed, int64_type(), "");
+    build_store(lc.builder, seed, state);
+}
+
+// a non-negative i32
+#[allow(dead_code)]
+pub fn build_rand(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    let random = get_random_function(lc);
+    let value = call_function(lc.builder, random, vec![], "");
+    let value = logical_shift_right_variable(lc.builder, value, const_int(int64_type(), 33), "");
+    build_cast(lc.builder, LLVMOpcode::LLVMTrunc, value, int32_type(), "")
+}
+
+// an i32 in [low, high). an empty range gives low
+#[allow(dead_code)]
+pub fn build_rand_range(
+    lc: &mut LLVMCreator,
+    low: *mut LLVMValue,
+    high: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let random = get_random_function(lc);
+    let builder = lc.builder;
+    let value = call_function(builder, random, vec![], "");
+
+    let wide_low = build_cast(builder, LLVMOpcode::LLVMSExt, low, int64_type(), "");
+    let wide_high = build_cast(builder, LLVMOpcode::LLVMSExt, high, int64_type(), "");
+    let span = sub_variable(builder, wide_high, wide_low, "");
+    let is_empty = build_int_sle(builder, span, const_int(int64_type(), 0), "");
+    let span = build_select(builder, is_empty, const_int(int64_type(), 1), span, "");
+    let offset = rem_variable(builder, value, span, "");
+    let offset = build_cast(builder, LLVMOpcode::LLVMTrunc, offset, int32_type(), "");
+    add_variable(builder, low, offset, "")
+}
+
+// milliseconds of a monotonic clock as an i64
+#[allow(dead_code)]
+pub fn build_clock_ms(lc: &mut LLVMCreator) -> *mut LLVMValue {
+    let builder = lc.builder;
+    // `struct timespec` is two i64s of seconds and nanoseconds on 64 bit linux
+    let time = build_alloca(builder, array_type(int64_type(), 2), "");
+    let time_pointer = build_cast(builder, LLVMOpcode::LLVMBitCast, time, pointer_type(), "");
+    call_function(
+        builder,
+        lc.built_ins["clock_gettime"],
+        vec![const_int(int32_type(), CLOCK_MONOTONIC), time_pointer],
+        "",
+    );
+
+    let mut fields = Vec::new();
+    for index in 0..2 {
+        let field = build_gep(
+            builder,
+            time,
+            vec![const_int(int32_type(), 0), const_int(int32_type(), index)],
+            "",
+        );
+        fields.push(build_load(builder, field, ""));
+    }
+    let milliseconds = multiple_variable(builder, fields[0], const_int(int64_type(), 1000), "");
+    let nanoseconds =
+        signed_divide_variable(builder, fields[1], const_int(int64_type(), 1_000_000), "");
+    add_variable(builder, milliseconds, nanoseconds, "")
+}
+
+// seeds with 42 and returns the i32 built by `build_value`
+#[allow(dead_code)]
+fn execute_random_test<F>(build_value: F) -> u64
+where
+    F: FnOnce(&mut LLVMCreator) -> *mut LLVMValue,
+{
+    let mut lc = LLVMCreator::new("test_module");
+    let main = setup_main(&mut lc);
+    build_seed(&mut lc, const_int(int32_type(), 42));
+    let value = build_value(&mut lc);
+    build_ret(lc.builder, value);
+    execute_test_ir_function(lc.module, main)
+}
+
+#[test]
+fn seeded_random_numbers() {
+    // the first values of splitmix64 seeded with 42
+    assert_eq!(execute_random_test(|lc| build_rand(lc)), 1_592_498_451);
+    assert_eq!(
+        execute_random_test(|lc| {
+            build_rand(lc);
+            build_rand(lc)
+        }),
+        343_404_953
+    );
+    let low = -10i32 as u64;
+    let range = execute_random_test(|lc| {
+        let low = const_int(int32_type(), low);
+        build_rand_range(lc, low, const_int(int32_type(), 10))
+    });
+    assert_eq!(range, 3);
+    let empty = execute_random_test(|lc| {
+        let low = const_int(int32_type(), 5);
+        build_rand_range(lc, low, const_int(int32_type(), 5))
+    });
+    assert_eq!(empty, 5);
+}
+
+#[test]
+fn monotonic_clock() {
+    let mut lc = LLVMCreator::new("test_module");
+    let main = setup_main(&mut lc);
+    let first = build_clock_ms(&mut lc);
+    let second = build_clock_ms(&mut lc);
+    let is_monotonic = build_int_sle(lc.builder, first, second, "");
+    let is_positive = build_int_sgt(lc.builder, first, const_int(int64_type(), 0), "");
+    let is_ok = and_variable(lc.builder, is_monotonic, is_positive, "");
+    let is_ok = build_cast(lc.builder, LLVMOpcode::LLVMZExt, is_ok, int32_type(), "");
+    build_ret(lc.builder, is_ok);
+    assert_eq!(execute_test_ir_function(lc.module, main), 1);
+}
@@ -8,6 +8,49 @@ pub fn add_global(module: *mut LLVMModule, llvm_type: *mut LLVMType, name: &str)
     unsafe { LLVMAddGlobal(module, llvm_type, c_string!(name).as_ptr()) }
 }
 
+#[allow(dead_code)]
+pub fn get_named_global(module: *mut LLVMModule, name: &str) -> *mut LLVMValue {
+    unsafe { LLVMGetNamedGlobal(module, c_string!(name).as_ptr()) }
+}
+
+// functions and global variables which the module defines, not only declares
+#[allow(dead_code)]
+pub fn get_definitions(module: *mut LLVMModule) -> Vec<*mut LLVMValue> {
+    let mut definitions = Vec::new();
+    unsafe {
+        let mut function = LLVMGetFirstFunction(module);
+        while function.is_null() == false {
+            if LLVMIsDeclaration(function) == 0 {
+                definitions.push(function);
+            }
+            function = LLVMGetNextFunction(function);
+        }
+        let mut global = LLVMGetFirstGlobal(module);
+        while global.is_null() == false {
+            if LLVMIsDeclaration(global) == 0 {
+                definitions.push(global);
+            }
+            global = LLVMGetNextGlobal(global);
+        }
+    }
+    definitions
+}
+
+#[allow(dead_code)]
+pub fn has_uses(value: *mut LLVMValue) -> bool {
+    unsafe { LLVMGetFirstUse(value).is_null() == false }
+}
+
+#[allow(dead_code)]
+pub fn get_value_name(value: *mut LLVMValue) -> String {
+    unsafe {
+        let mut length = 0;
+        let name = LLVMGetValueName2(value, &mut length);
+        let bytes = ::std::slice::from_raw_parts(name as *const u8, length);
+        String::from_utf8_lossy(bytes).to_string()
+    }
+}
+
 #[allow(dead_code)]
 pub fn set_linkage(value: *mut LLVMValue, linkage: LLVMLinkage) {
     unsafe {
@@ -15,6 +58,11 @@ pub fn set_linkage(value: *mut LLVMValue, linkage: LLVMLinkage) {
     };
 }
 
+#[allow(dead_code)]
+pub fn get_linkage(value: *mut LLVMValue) -> LLVMLinkage {
+    unsafe { LLVMGetLinkage(value) }
+}
+
 #[allow(dead_code)]
 pub fn set_initializer(target: *mut LLVMValue, value: *mut LLVMValue) {
     unsafe {
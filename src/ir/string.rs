@@ -4,18 +4,19 @@ use llvm_sys::core::*;
 use llvm_sys::LLVMLinkage::*;
 use llvm_sys::*;
 
+use ir::arithmetic::*;
+use ir::condition::*;
 use ir::const_value::*;
 use ir::creator::*;
+use ir::function::*;
+use ir::llvm_struct::*;
 use ir::llvm_type::*;
 use ir::operate::*;
 use ir::scope::*;
 
 #[allow(dead_code)]
 pub fn const_string_in_context(context: *mut LLVMContext, input_str: String) -> *mut LLVMValue {
-    let temp_str = input_str
-        .replace("\\n", "\u{0000A}")
-        .replace("\\r", "\u{000D}")
-        + "\0";
+    let temp_str = input_str + "\0";
     let byte = temp_str.as_bytes();
     let length = byte.len() as u32;
     unsafe { LLVMConstStringInContext(context, byte.as_ptr() as *const _, length, 1) }
@@ -23,7 +24,9 @@ pub fn const_string_in_context(context: *mut LLVMContext, input_str: String) ->
 
 #[allow(dead_code)]
 pub fn codegen_string(lc: &mut LLVMCreator, input_str: &str, name: &str) -> *mut LLVMValue {
-    let str_val = const_string_in_context(lc.context, input_str.to_string());
+    // types are created in the module's context, so the constant has to live there too
+    let context = unsafe { LLVMGetModuleContext(lc.module) };
+    let str_val = const_string_in_context(context, input_str.to_string());
     let global_str_val = add_global(lc.module, type_of(str_val), name);
     set_linkage(global_str_val, LLVMPrivateLinkage);
     set_initializer(global_str_val, str_val);
@@ -35,7 +38,9 @@ pub fn codegen_string(lc: &mut LLVMCreator, input_str: &str, name: &str) -> *mut
 
 #[allow(dead_code)]
 pub fn codegen_string_gep(lc: &mut LLVMCreator, input_str: &str, name: &str) -> *mut LLVMValue {
-    let str_val = const_string_in_context(lc.context, input_str.to_string());
+    // types are created in the module's context, so the constant has to live there too
+    let context = unsafe { LLVMGetModuleContext(lc.module) };
+    let str_val = const_string_in_context(context, input_str.to_string());
     let global_str_val = add_global(lc.module, type_of(str_val), name);
     set_linkage(global_str_val, LLVMPrivateLinkage);
     set_initializer(global_str_val, str_val);
@@ -54,3 +59,200 @@ pub fn codegen_string_gep(lc: &mut LLVMCreator, input_str: &str, name: &str) ->
         );
     }
 }
+
+// strings made at runtime are malloc'd here and never freed. a string value is copied by
+// pointer into every binding, array and argument, so no single place owns it, and the memory
+// is only returned when the program exits
+#[allow(dead_code)]
+pub fn build_string_value(
+    lc: &mut LLVMCreator,
+    pointer: *mut LLVMValue,
+    length: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let size = unsafe { LLVMSizeOf(string_struct_type()) };
+    let memory = call_function(lc.builder, lc.built_ins["malloc"], vec![size], "");
+    let string_value = cast_type(lc.builder, memory, string_type(), "");
+    set_field_value(lc.builder, string_value, 0, pointer, "");
+    set_field_value(lc.builder, string_value, 1, length, "");
+    string_value
+}
+
+// a literal is a constant string struct, so it does not allocate.
+// globals are named because the execution engine maps unnamed ones to the same address
+#[allow(dead_code)]
+pub fn codegen_string_value(lc: &mut LLVMCreator, input_str: &str) -> *mut LLVMValue {
+    let pointer = codegen_string(lc, input_str, ".str");
+    let length = const_int(int32_type(), input_str.len() as u64);
+    let mut fields = [pointer, length];
+    let struct_value = unsafe { LLVMConstStruct(fields.as_mut_ptr(), fields.len() as u32, 0) };
+
+    let global_struct_value = add_global(lc.module, string_struct_type(), ".string");
+    set_linkage(global_struct_value, LLVMPrivateLinkage);
+    set_initializer(global_struct_value, struct_value);
+    set_global_constant(global_struct_value);
+    global_struct_value
+}
+
+// wraps a C string, which is not copied, into a string value. null gives an empty string
+#[allow(dead_code)]
+pub fn build_c_string_value(lc: &mut LLVMCreator, pointer: *mut LLVMValue) -> *mut LLVMValue {
+    let is_null = build_int_eq(lc.builder, pointer, const_null(pointer_type()), "");
+    let empty = codegen_string(lc, "", ".str");
+    let pointer = build_select(lc.builder, is_null, empty, pointer, "");
+    let length = call_function(lc.builder, lc.built_ins["strlen"], vec![pointer], "");
+    let length = build_cast(lc.builder, LLVMOpcode::LLVMTrunc, length, int32_type(), "");
+    build_string_value(lc, pointer, length)
+}
+
+#[allow(dead_code)]
+pub fn get_string_pointer(
+    builder: *mut LLVMBuilder,
+    string_value: *mut LLVMValue,
+) -> *mut LLVMValue {
+    build_load(builder, get_field_value(builder, string_value, 0, ""), "")
+}
+
+#[allow(dead_code)]
+pub fn get_string_length(
+    builder: *mut LLVMBuilder,
+    string_value: *mut LLVMValue,
+) -> *mut LLVMValue {
+    build_load(builder, get_field_value(builder, string_value, 1, ""), "")
+}
+
+// allocates length + 1 bytes so that the result is null terminated
+fn build_string_buffer(lc: &mut LLVMCreator, length: *mut LLVMValue) -> *mut LLVMValue {
+    let size = add_variable(lc.builder, length, const_int(int32_type(), 1), "");
+    let size = build_cast(lc.builder, LLVMOpcode::LLVMZExt, size, int64_type(), "");
+    let buffer = call_function(lc.builder, lc.built_ins["malloc"], vec![size], "");
+    let end = build_gep(lc.builder, buffer, vec![length], "");
+    build_store(lc.builder, const_int(int8_type(), 0), end);
+    buffer
+}
+
+fn build_copy(
+    lc: &mut LLVMCreator,
+    destination: *mut LLVMValue,
+    source: *mut LLVMValue,
+    length: *mut LLVMValue,
+) {
+    let size = build_cast(lc.builder, LLVMOpcode::LLVMZExt, length, int64_type(), "");
+    call_function(
+        lc.builder,
+        lc.built_ins["memcpy"],
+        vec![destination, source, size],
+        "",
+    );
+}
+
+#[allow(dead_code)]
+pub fn build_string_concat(
+    lc: &mut LLVMCreator,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let left_pointer = get_string_pointer(lc.builder, left);
+    let left_length = get_string_length(lc.builder, left);
+    let right_pointer = get_string_pointer(lc.builder, right);
+    let right_length = get_string_length(lc.builder, right);
+
+    let length = add_variable(lc.builder, left_length, right_length, "");
+    let buffer = build_string_buffer(lc, length);
+    build_copy(lc, buffer, left_pointer, left_length);
+    let middle = build_gep(lc.builder, buffer, vec![left_length], "");
+    build_copy(lc, middle, right_pointer, right_length);
+
+    build_string_value(lc, buffer, length)
+}
+
+// returns an i32 ordered like strcmp. strings may hold \0, so the bytes are compared up to the
+// shorter length by memcmp, and a string is less than a longer one which starts with it
+#[allow(dead_code)]
+pub fn build_string_compare(
+    lc: &mut LLVMCreator,
+    left: *mut LLVMValue,
+    right: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let left_pointer = get_string_pointer(lc.builder, left);
+    let right_pointer = get_string_pointer(lc.builder, right);
+    let left_length = get_string_length(lc.builder, left);
+    let right_length = get_string_length(lc.builder, right);
+    let is_shorter = build_int_ult(lc.builder, left_length, right_length, "");
+    let is_longer = build_int_ugt(lc.builder, left_length, right_length, "");
+    let shorter = build_select(lc.builder, is_shorter, left_length, right_length, "");
+    let shorter = build_cast(lc.builder, LLVMOpcode::LLVMZExt, shorter, int64_type(), "");
+    let compared = call_function(
+        lc.builder,
+        lc.built_ins["memcmp"],
+        vec![left_pointer, right_pointer, shorter],
+        "",
+    );
+
+    let is_shorter = build_cast(
+        lc.builder,
+        LLVMOpcode::LLVMZExt,
+        is_shorter,
+        int32_type(),
+        "",
+    );
+    let is_longer = build_cast(
+        lc.builder,
+        LLVMOpcode::LLVMZExt,
+        is_longer,
+        int32_type(),
+        "",
+    );
+    let length_order = sub_variable(lc.builder, is_longer, is_shorter, "");
+    let is_equal = build_int_eq(lc.builder, compared, const_int(int32_type(), 0), "");
+    build_select(lc.builder, is_equal, length_order, compared, "")
+}
+
+#[allow(dead_code)]
+pub fn build_string_index(
+    builder: *mut LLVMBuilder,
+    string_value: *mut LLVMValue,
+    index: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let pointer = get_string_pointer(builder, string_value);
+    let element = build_gep(builder, pointer, vec![index], "");
+    build_load(builder, element, "")
+}
+
+// copies the bytes in [start, end) into a new string
+#[allow(dead_code)]
+pub fn build_string_slice(
+    lc: &mut LLVMCreator,
+    string_value: *mut LLVMValue,
+    start: *mut LLVMValue,
+    end: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let pointer = get_string_pointer(lc.builder, string_value);
+    let length = sub_variable(lc.builder, end, start, "");
+    let buffer = build_string_buffer(lc, length);
+    let source = build_gep(lc.builder, pointer, vec![start], "");
+    build_copy(lc, buffer, source, length);
+
+    build_string_value(lc, buffer, length)
+}
+
+// formats the arguments into a new string with snprintf.
+// the result is cut at max_length bytes, so it should be an upper bound of the formatted length
+#[allow(dead_code)]
+pub fn build_string_format(
+    lc: &mut LLVMCreator,
+    format: &str,
+    mut arguments: Vec<*mut LLVMValue>,
+    max_length: *mut LLVMValue,
+) -> *mut LLVMValue {
+    let buffer = build_string_buffer(lc, max_length);
+    let size = add_variable(lc.builder, max_length, const_int(int32_type(), 1), "");
+    let size = build_cast(lc.builder, LLVMOpcode::LLVMZExt, size, int64_type(), "");
+    let format_pointer = codegen_string(lc, format, ".str");
+    let mut snprintf_arguments = vec![buffer, size, format_pointer];
+    snprintf_arguments.append(&mut arguments);
+    call_function(lc.builder, lc.built_ins["snprintf"], snprintf_arguments, "");
+
+    let length = call_function(lc.builder, lc.built_ins["strlen"], vec![buffer], "");
+    let length = build_cast(lc.builder, LLVMOpcode::LLVMTrunc, length, int32_type(), "");
+    build_string_value(lc, buffer, length)
+}
@@ -0,0 +1,223 @@
+use std::ffi::{CStr, CString};
+use std::path::Path;
+
+use llvm_sys::core::*;
+use llvm_sys::target::*;
+use llvm_sys::target_machine::*;
+use llvm_sys::*;
+
+#[allow(unused_imports)]
+use ir::block::*;
+
+#[allow(unused_imports)]
+use ir::const_value::*;
+
+#[allow(unused_imports)]
+use ir::creator::*;
+
+#[allow(unused_imports)]
+use ir::function::*;
+
+#[allow(unused_imports)]
+use ir::llvm_type::*;
+
+#[allow(unused_imports)]
+use ir::operate::*;
+
+// copies a message allocated by LLVM and frees it
+pub fn take_message(message: *mut i8) -> String {
+    if message.is_null() {
+        return String::new();
+    }
+    unsafe {
+        let string = CStr::from_ptr(message).to_string_lossy().into_owned();
+        LLVMDisposeMessage(message);
+        string
+    }
+}
+
+#[allow(dead_code)]
+pub fn initialize_native_target() -> Result<(), String> {
+    unsafe {
+        if LLVM_InitializeNativeTarget() != 0 || LLVM_InitializeNativeAsmPrinter() != 0 {
+            return Err("failed to initialize the native target".to_string());
+        }
+    }
+    Ok(())
+}
+
+// every target LLVM was built with, for cross compilation
+#[allow(dead_code)]
+pub fn initialize_all_targets() {
+    unsafe {
+        LLVM_InitializeAllTargetInfos();
+        LLVM_InitializeAllTargets();
+        LLVM_InitializeAllTargetMCs();
+        LLVM_InitializeAllAsmPrinters();
+    }
+}
+
+#[allow(dead_code)]
+pub fn default_target_triple() -> String {
+    take_message(unsafe { LLVMGetDefaultTargetTriple() })
+}
+
+// `aarch64-linux-gnu` gives `aarch64-unknown-linux-gnu`
+#[allow(dead_code)]
+pub fn normalize_target_triple(triple: &str) -> String {
+    take_message(unsafe { LLVMNormalizeTargetTriple(c_string!(triple).as_ptr()) })
+}
+
+#[allow(dead_code)]
+pub fn host_cpu_name() -> String {
+    take_message(unsafe { LLVMGetHostCPUName() })
+}
+
+// whether LLVM was built with a backend for the triple
+#[allow(dead_code)]
+pub fn has_target(triple: &str) -> bool {
+    let mut target: LLVMTargetRef = 0 as LLVMTargetRef;
+    let mut error: *mut i8 = 0 as *mut i8;
+    let found =
+        unsafe { LLVMGetTargetFromTriple(c_string!(triple).as_ptr(), &mut target, &mut error) };
+    take_message(error);
+    found == 0
+}
+
+// position independent code, so that objects can go into shared libraries
+#[allow(dead_code)]
+pub fn create_target_machine(
+    triple: &str,
+    cpu: &str,
+    level: LLVMCodeGenOptLevel,
+) -> Result<LLVMTargetMachineRef, String> {
+    let triple = c_string!(triple);
+    let mut target: LLVMTargetRef = 0 as LLVMTargetRef;
+    let mut error: *mut i8 = 0 as *mut i8;
+    unsafe {
+        if LLVMGetTargetFromTriple(triple.as_ptr(), &mut target, &mut error) != 0 {
+            return Err(take_message(error));
+        }
+        Ok(LLVMCreateTargetMachine(
+            target,
+            triple.as_ptr(),
+            c_string!(cpu).as_ptr(),
+            c_string!("").as_ptr(),
+            level,
+            LLVMRelocMode::LLVMRelocPIC,
+            LLVMCodeModel::LLVMCodeModelDefault,
+        ))
+    }
+}
+
+#[allow(dead_code)]
+pub fn get_target_machine_triple(machine: LLVMTargetMachineRef) -> String {
+    take_message(unsafe { LLVMGetTargetMachineTriple(machine) })
+}
+
+#[allow(dead_code)]
+pub fn get_target_machine_cpu(machine: LLVMTargetMachineRef) -> String {
+    take_message(unsafe { LLVMGetTargetMachineCPU(machine) })
+}
+
+// bytes of a pointer, and so of size_t, in the data layout of the machine
+#[allow(dead_code)]
+pub fn get_target_pointer_size(machine: LLVMTargetMachineRef) -> u32 {
+    unsafe {
+        let data_layout = LLVMCreateTargetDataLayout(machine);
+        let size = LLVMPointerSize(data_layout);
+        LLVMDisposeTargetData(data_layout);
+        size
+    }
+}
+
+#[allow(dead_code)]
+pub fn dispose_target_machine(machine: LLVMTargetMachineRef) {
+    unsafe { LLVMDisposeTargetMachine(machine) }
+}
+
+// the module takes the triple and the data layout of the machine
+#[allow(dead_code)]
+pub fn set_module_target(module: *mut LLVMModule, machine: LLVMTargetMachineRef) {
+    unsafe {
+        let triple = LLVMGetTargetMachineTriple(machine);
+        LLVMSetTarget(module, triple);
+        LLVMDisposeMessage(triple);
+
+        let data_layout = LLVMCreateTargetDataLayout(machine);
+        LLVMSetModuleDataLayout(module, data_layout);
+        LLVMDisposeTargetData(data_layout);
+    }
+}
+
+#[allow(dead_code)]
+pub fn get_module_target(module: *mut LLVMModule) -> String {
+    unsafe { CStr::from_ptr(LLVMGetTarget(module)) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[allow(dead_code)]
+pub fn emit_to_file<P: AsRef<Path>>(
+    machine: LLVMTargetMachineRef,
+    module: *mut LLVMModule,
+    path: P,
+    file_type: LLVMCodeGenFileType,
+) -> Result<(), String> {
+    let path = path
+        .as_ref()
+        .to_str()
+        .ok_or_else(|| "Did not find a valid Unicode path string".to_string())?;
+    let path = c_string!(path);
+    let mut error: *mut i8 = 0 as *mut i8;
+    // the code generator changes the module it runs on, so every output gets a fresh copy
+    let result = unsafe {
+        let copy = LLVMCloneModule(module);
+        let result = LLVMTargetMachineEmitToFile(
+            machine,
+            copy,
+            path.as_ptr() as *mut _,
+            file_type,
+            &mut error,
+        );
+        LLVMDisposeModule(copy);
+        result
+    };
+    if result != 0 {
+        return Err(take_message(error));
+    }
+    Ok(())
+}
+
+#[test]
+fn native_object_file() {
+    let mut lc = LLVMCreator::new("test_module");
+    let function_type = function_type(int32_type(), &mut []);
+    let function = add_function(lc.module, function_type, "answer");
+    let block = append_basic_block_in_context(lc.context, function, "entry");
+    build_position_at_end(lc.builder, block);
+    build_ret(lc.builder, const_int(int32_type(), 42));
+
+    initialize_native_target().unwrap();
+    let triple = default_target_triple();
+    let machine =
+        create_target_machine(&triple, "", LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault).unwrap();
+    set_module_target(lc.module, machine);
+    assert_eq!(get_module_target(lc.module), triple);
+
+    let print_module = |module| unsafe { take_message(LLVMPrintModuleToString(module)) };
+    let before = print_module(lc.module);
+    let path = ::std::env::temp_dir().join(format!("native_object_{}.o", ::std::process::id()));
+    for file_type in vec![
+        LLVMCodeGenFileType::LLVMAssemblyFile,
+        LLVMCodeGenFileType::LLVMObjectFile,
+    ] {
+        emit_to_file(machine, lc.module, &path, file_type).unwrap();
+    }
+    dispose_target_machine(machine);
+    assert_eq!(print_module(lc.module), before);
+
+    let object = ::std::fs::read(&path).unwrap();
+    let _ = ::std::fs::remove_file(&path);
+    assert_eq!(&object[..4], b"\x7fELF");
+}
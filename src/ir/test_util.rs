@@ -1,5 +1,9 @@
 use std::ffi::CString;
+use std::mem;
 
+use libc::c_void;
+
+use llvm_sys::core::*;
 use llvm_sys::execution_engine::*;
 use llvm_sys::*;
 
@@ -8,32 +12,55 @@ use ir::creator::*;
 use ir::function::*;
 use ir::llvm_type::*;
 use ir::operate::*;
+use ir::target::*;
 use ir::validate::*;
 
 const LLVM_ERROR: i32 = 1;
 
+#[allow(dead_code)]
+pub const TEST_ARGUMENTS: [&str; 2] = ["test", "--verbose"];
+
+// a fresh MCJIT per module. the code is compiled for the host, so external functions are
+// resolved by their symbol names and intrinsics are lowered as they are in an executable
 #[allow(dead_code)]
 fn create_llvm_engine(module: *mut LLVMModule) -> LLVMExecutionEngineRef {
-    let error = 0 as *mut i8;
+    validate_module(module);
+    if let Err(message) = initialize_native_target() {
+        panic!("Execution error: {}", message);
+    }
+
     let mut engine: LLVMExecutionEngineRef = 0 as LLVMExecutionEngineRef;
+    let mut error = 0 as *mut i8;
     let ok = unsafe {
-        let mut error = 0 as *mut i8;
-        let buf: *mut *mut i8 = &mut error;
-        let engine_ref: *mut LLVMExecutionEngineRef = &mut engine;
-        LLVMLinkInInterpreter();
-        LLVMCreateInterpreterForModule(engine_ref, module, buf)
+        LLVMLinkInMCJIT();
+        let mut options: LLVMMCJITCompilerOptions = mem::zeroed();
+        let size = mem::size_of::<LLVMMCJITCompilerOptions>();
+        LLVMInitializeMCJITCompilerOptions(&mut options, size);
+        LLVMCreateMCJITCompilerForModule(&mut engine, module, &mut options, size, &mut error)
     };
     if ok == LLVM_ERROR {
         panic!("Execution error: {}", string_from_raw!(error));
     }
-    validate_module(module);
     engine
 }
 
 #[allow(dead_code)]
 pub fn execute_test_ir_function(module: *mut LLVMModule, target_function: *mut LLVMValue) -> u64 {
     let engine = create_llvm_engine(module);
-    let mut params = [];
+
+    // a main which takes argc and argv gets TEST_ARGUMENTS
+    let arguments: Vec<CString> = TEST_ARGUMENTS.iter().map(|arg| c_string!(*arg)).collect();
+    let mut argv: Vec<*const i8> = arguments.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(0 as *const i8);
+    let mut params = match unsafe { LLVMCountParams(target_function) } {
+        2 => unsafe {
+            vec![
+                LLVMCreateGenericValueOfInt(int32_type(), TEST_ARGUMENTS.len() as u64, 0),
+                LLVMCreateGenericValueOfPointer(argv.as_mut_ptr() as *mut c_void),
+            ]
+        },
+        _ => vec![],
+    };
     let func_result = run_function(
         engine,
         target_function,
@@ -42,6 +69,10 @@ pub fn execute_test_ir_function(module: *mut LLVMModule, target_function: *mut L
     );
 
     unsafe {
+        // the module still belongs to the creator, so the engine must not dispose it
+        let mut removed_module = 0 as *mut LLVMModule;
+        let mut error = 0 as *mut i8;
+        LLVMRemoveModule(engine, module, &mut removed_module, &mut error);
         LLVMDisposeExecutionEngine(engine);
         LLVMGenericValueToInt(func_result, 0)
     }
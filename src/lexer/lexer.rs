@@ -1,30 +1,69 @@
+use std::borrow::Cow;
+
+use unicode_xid::UnicodeXID;
+
 use lexer::token::*;
 
+const INTEGER_SUFFIXES: [&'static str; 8] = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
+
 #[derive(Debug)]
 pub struct Lexer<'a> {
+    pub input: &'a str,
     pub bytes: &'a [u8],
     pub position: usize,
+    pub token_start: usize,
     pub current_row: usize,
+    pub errors: Vec<String>,
+    pub comments: Vec<Token<'a>>,
+    // brace depth inside each open `${ ... }`, innermost last
+    pub interpolation_depths: Vec<usize>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Lexer {
         let bytes = input.as_bytes();
         Lexer {
+            input: input,
             bytes: bytes,
             position: 0,
+            token_start: 0,
             current_row: 0,
+            errors: Vec::new(),
+            comments: Vec::new(),
+            interpolation_depths: Vec::new(),
         }
     }
 
-    pub fn create_eof_token(&mut self) -> Token {
-        Token::new(TokenType::Eof, String::new(), self.position)
+    pub fn create_eof_token(&mut self) -> Token<'a> {
+        Token::new(TokenType::Eof, Cow::Borrowed(""), self.position, 0)
+    }
+
+    // columns count characters from the start of the line, not bytes
+    pub fn get_column(&self, position: usize) -> usize {
+        let line_start = match self.input[..position].rfind('\n') {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        self.input[line_start..position].chars().count()
+    }
+
+    pub fn push_error(&mut self, message: String, position: usize) {
+        let row = self.input[..position].matches('\n').count();
+        let column = self.get_column(position);
+        self.errors
+            .push(format!("{}. row: {}, column: {}", message, row, column));
+    }
+
+    pub fn get_next_unicode_char(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
     }
 
     pub fn handle_reserved_word(&self, word: &str, token: TokenType) -> TokenType {
         match word {
             "while" => TokenType::While,
             "let" => TokenType::Let,
+            "as" => TokenType::As,
+            "mut" => TokenType::Mut,
             "fn" => TokenType::Fn,
             "true" => TokenType::True,
             "false" => TokenType::False,
@@ -33,8 +72,19 @@ impl<'a> Lexer<'a> {
             "else" => TokenType::Else,
             "return" => TokenType::Return,
             "break" => TokenType::Break,
+            "extern" => TokenType::Extern,
+            "export" => TokenType::Export,
             "boolean" => TokenType::LLVMTokenType(LLVMTokenType::Boolean),
             "int" => TokenType::LLVMTokenType(LLVMTokenType::Integer),
+            "float" => TokenType::LLVMTokenType(LLVMTokenType::Float),
+            "i8" => TokenType::LLVMTokenType(LLVMTokenType::I8),
+            "i16" => TokenType::LLVMTokenType(LLVMTokenType::I16),
+            "i32" => TokenType::LLVMTokenType(LLVMTokenType::I32),
+            "i64" => TokenType::LLVMTokenType(LLVMTokenType::I64),
+            "u8" => TokenType::LLVMTokenType(LLVMTokenType::U8),
+            "u16" => TokenType::LLVMTokenType(LLVMTokenType::U16),
+            "u32" => TokenType::LLVMTokenType(LLVMTokenType::U32),
+            "u64" => TokenType::LLVMTokenType(LLVMTokenType::U64),
             "string" => TokenType::LLVMTokenType(LLVMTokenType::String),
             "null" => TokenType::LLVMTokenType(LLVMTokenType::Null),
             _ => token,
@@ -48,158 +98,478 @@ impl<'a> Lexer<'a> {
         None
     }
 
+    // block comments nest, e.g. /* outer /* inner */ still outer */
     pub fn consume_comment(&mut self) {
-        loop {
+        let start = self.position - 2;
+        let start_row = self.current_row;
+        let mut depth = 1;
+        while depth > 0 {
             if let Some(byte) = self.get_next_char() {
                 self.position += 1;
-                if byte == b'*' {
-                    if let Some(next) = self.get_next_char() {
-                        if next == b'/' {
-                            self.position += 1;
-                            break;
-                        }
-                    } else {
-                        break;
+                match (byte, self.get_next_char()) {
+                    (b'*', Some(b'/')) => {
+                        self.position += 1;
+                        depth -= 1;
+                    }
+                    (b'/', Some(b'*')) => {
+                        self.position += 1;
+                        depth += 1;
                     }
+                    (b'\n', _) => self.current_row += 1,
+                    _ => {}
                 }
             } else {
+                self.push_error("unterminated block comment".to_string(), start);
                 break;
             }
         }
+        self.push_comment(start, start_row);
     }
 
-    pub fn consumue_character(&mut self, first_byte: u8, mut num_flag: bool) -> Token {
-        let mut temp_vec: Vec<u8> = Vec::new();
-        temp_vec.push(first_byte);
-        loop {
-            if let Some(byte) = self.get_next_char() {
-                let break_flg = match byte {
-                    b'0'...b'9' => {
+    pub fn consume_line_comment(&mut self) {
+        let start = self.position - 2;
+        while let Some(byte) = self.get_next_char() {
+            if byte == b'\n' {
+                break;
+            }
+            self.position += 1;
+        }
+        let row = self.current_row;
+        self.push_comment(start, row);
+    }
+
+    // comments are not passed to the parser but kept for tooling
+    pub fn push_comment(&mut self, start: usize, row: usize) {
+        let value = Cow::Borrowed(&self.input[start..self.position]);
+        let column = self.get_column(start);
+        self.comments
+            .push(Token::new(TokenType::Comment, value, row, column));
+    }
+
+    pub fn get_char_at(&self, position: usize) -> Option<u8> {
+        if position < self.bytes.len() {
+            return Some(self.bytes[position]);
+        }
+        None
+    }
+
+    pub fn is_digit_at(&self, position: usize) -> bool {
+        match self.get_char_at(position) {
+            Some(b'0'...b'9') => true,
+            _ => false,
+        }
+    }
+
+    // e.g. 1e3, 1e-3, 1E+3
+    pub fn is_exponent_at(&self, position: usize) -> bool {
+        match self.get_char_at(position) {
+            Some(b'+') | Some(b'-') => self.is_digit_at(position + 1),
+            _ => self.is_digit_at(position),
+        }
+    }
+
+    // e.g. 10u8, 300i64
+    pub fn get_integer_suffix_length(&self, position: usize) -> Option<usize> {
+        for suffix in INTEGER_SUFFIXES.iter() {
+            let end = position + suffix.len();
+            if end > self.bytes.len() || &self.bytes[position..end] != suffix.as_bytes() {
+                continue;
+            }
+            match self.get_char_at(end) {
+                Some(b'a'...b'z') | Some(b'A'...b'Z') | Some(b'0'...b'9') | Some(b'_') => {}
+                _ => return Some(suffix.len()),
+            }
+        }
+        None
+    }
+
+    pub fn consumue_character(&mut self) -> Token<'a> {
+        while let Some(next_char) = self.get_next_unicode_char() {
+            if UnicodeXID::is_xid_continue(next_char) == false {
+                break;
+            }
+            self.position += next_char.len_utf8();
+        }
+        self.create_token(TokenType::Identifier)
+    }
+
+    // e.g. 0xFF, 0b1010, 0o17
+    pub fn get_radix_at(&self, position: usize) -> Option<u32> {
+        match self.get_char_at(position) {
+            Some(b'x') => Some(16),
+            Some(b'b') => Some(2),
+            Some(b'o') => Some(8),
+            _ => None,
+        }
+    }
+
+    pub fn consume_number(&mut self, first_byte: u8) -> Token<'a> {
+        let mut float_flag = false;
+        let mut exponent_flag = false;
+        let mut malformed_flag = false;
+
+        let radix = if first_byte == b'0' {
+            self.get_radix_at(self.position)
+        } else {
+            None
+        };
+        if radix.is_some() {
+            self.position += 1;
+        }
+
+        while let Some(byte) = self.get_next_char() {
+            let is_radix_digit = (byte as char).is_digit(radix.unwrap_or(10));
+            let break_flg = match byte {
+                _ if is_radix_digit && float_flag == false || byte.is_ascii_digit() => {
+                    malformed_flag = malformed_flag || is_radix_digit == false;
+                    self.position += 1;
+                    false
+                }
+                b'_' => {
+                    self.position += 1;
+                    false
+                }
+                b'.' if radix.is_none()
+                    && float_flag == false
+                    && self.is_digit_at(self.position + 1) =>
+                {
+                    self.position += 1;
+                    float_flag = true;
+                    false
+                }
+                b'e' | b'E'
+                    if radix.is_none()
+                        && exponent_flag == false
+                        && self.is_exponent_at(self.position + 1) =>
+                {
+                    self.position += 1;
+                    if let Some(b'+') | Some(b'-') = self.get_next_char() {
                         self.position += 1;
-                        temp_vec.push(byte);
-                        false
                     }
-                    b'a'...b'z' | b'A'...b'Z' => {
+                    float_flag = true;
+                    exponent_flag = true;
+                    false
+                }
+                b'i' | b'u' if float_flag == false => {
+                    if let Some(length) = self.get_integer_suffix_length(self.position) {
+                        let end = self.position + length;
+                        self.position = end;
+                        true
+                    } else {
                         self.position += 1;
-                        temp_vec.push(byte);
-                        num_flag = false;
+                        malformed_flag = true;
                         false
                     }
-                    _ => true,
-                };
-
-                if break_flg == true {
-                    break;
                 }
-            } else {
+                b'a'...b'z' | b'A'...b'Z' => {
+                    self.position += 1;
+                    malformed_flag = true;
+                    false
+                }
+                _ => true,
+            };
+
+            if break_flg == true {
                 break;
             }
         }
 
-        let token_type = if num_flag == true {
-            TokenType::Digit
+        // a radix prefix needs at least one digit, e.g. `0x` alone is malformed
+        if radix.is_some()
+            && self.input[self.token_start + 2..self.position]
+                .chars()
+                .all(|c| c == '_')
+        {
+            malformed_flag = true;
+        }
+
+        let token_type = if malformed_flag == true {
+            let start = self.token_start;
+            let message = format!(
+                "invalid numeric literal {}",
+                &self.input[start..self.position]
+            );
+            self.push_error(message, start);
+            TokenType::Illegal
+        } else if float_flag == true {
+            TokenType::Float
         } else {
-            TokenType::Identifier
+            TokenType::Digit
         };
 
-        self.create_token_by_value(token_type, temp_vec)
+        self.create_token(token_type)
     }
 
-    pub fn create_token_by_value(&mut self, token: TokenType, value_vec: Vec<u8>) -> Token {
-        let ret_string = String::from_utf8(value_vec).unwrap();
+    // the token borrows its text from the input
+    pub fn create_token(&mut self, token: TokenType) -> Token<'a> {
+        let input = self.input;
+        let value = &input[self.token_start..self.position];
+        self.create_token_with_value(
+            self.handle_reserved_word(value, token),
+            Cow::Borrowed(value),
+        )
+    }
+
+    pub fn create_token_with_value(&mut self, token: TokenType, value: Cow<'a, str>) -> Token<'a> {
         Token::new(
-            self.handle_reserved_word(&ret_string, token),
-            ret_string.to_owned(),
+            token,
+            value,
             self.current_row,
+            self.get_column(self.token_start),
         )
     }
 
-    pub fn consume_slash(&mut self, target_token: Token) -> (Token, bool) {
+    pub fn consume_slash(&mut self, target_token: Token<'a>) -> (Token<'a>, bool) {
         if let Some(next) = self.get_next_char() {
             if next == b'*' {
                 self.position += 1;
                 self.consume_comment();
                 return (target_token, false);
             }
+            if next == b'/' {
+                self.position += 1;
+                self.consume_line_comment();
+                return (target_token, false);
+            }
         }
-        (
-            self.create_token_by_value(TokenType::Divide, vec![b'/']),
-            true,
-        )
+        (self.create_token(TokenType::Divide), true)
     }
 
-    pub fn consume_equal(&mut self) -> Token {
+    pub fn consume_equal(&mut self) -> Token<'a> {
         if let Some(next) = self.get_next_char() {
             if next == b'=' {
                 self.position += 1;
-                return self.create_token_by_value(TokenType::Eq, vec![b'=', b'=']);
+                return self.create_token(TokenType::Eq);
+            }
+        }
+        self.create_token(TokenType::Assign)
+    }
+
+    pub fn consume_period(&mut self) -> Token<'a> {
+        if self.bytes[self.position..].starts_with(b"..") {
+            self.position += 2;
+            return self.create_token(TokenType::Ellipsis);
+        }
+        self.create_token(TokenType::Period)
+    }
+
+    pub fn consume_asterisk(&mut self) -> Token<'a> {
+        if let Some(next) = self.get_next_char() {
+            if next == b'*' {
+                self.position += 1;
+                return self.create_token(TokenType::Power);
             }
         }
-        self.create_token_by_value(TokenType::Assign, vec![b'='])
+        self.create_token(TokenType::Multiply)
     }
 
-    pub fn consume_ban(&mut self) -> Token {
+    pub fn consume_ban(&mut self) -> Token<'a> {
         if let Some(next) = self.get_next_char() {
             if next == b'=' {
                 self.position += 1;
-                return self.create_token_by_value(TokenType::NotEq, vec![b'!', b'=']);
+                return self.create_token(TokenType::NotEq);
             }
         }
-        self.create_token_by_value(TokenType::Bang, vec![b'!'])
+        self.create_token(TokenType::Bang)
     }
 
-    pub fn consume_lt(&mut self) -> Token {
+    pub fn consume_lt(&mut self) -> Token<'a> {
         if let Some(next) = self.get_next_char() {
             if next == b'=' {
                 self.position += 1;
-                return self.create_token_by_value(TokenType::Lte, vec![b'<', b'=']);
+                return self.create_token(TokenType::Lte);
             }
         }
-        self.create_token_by_value(TokenType::Lt, vec![b'<'])
+        self.create_token(TokenType::Lt)
     }
 
-    pub fn consume_gt(&mut self) -> Token {
+    pub fn consume_gt(&mut self) -> Token<'a> {
         if let Some(next) = self.get_next_char() {
             if next == b'=' {
                 self.position += 1;
-                return self.create_token_by_value(TokenType::Gte, vec![b'>', b'=']);
+                return self.create_token(TokenType::Gte);
             }
         }
-        self.create_token_by_value(TokenType::Gt, vec![b'>'])
+        self.create_token(TokenType::Gt)
     }
 
-    pub fn consume_string(&mut self) -> Token {
-        let mut char_vec = Vec::new();
+    // a string is split at each `${ expression }`, e.g. "a${x}b${y}c" is lexed as
+    // StringStart(a), x, StringMiddle(b), y, StringEnd(c).
+    // `resumed` is true when the string continues after the `}` of an interpolation
+    pub fn consume_string(&mut self, resumed: bool) -> Token<'a> {
+        // only a string with escape sequences needs its own buffer
+        let mut decoded: Option<Vec<u8>> = None;
+        let start = self.token_start + 1;
+        let mut end = self.position;
+        let mut interpolation_flag = false;
         loop {
             if let Some(next_char) = self.get_next_char() {
                 self.position += 1;
-                if next_char == b'"' {
-                    break;
+                match next_char {
+                    b'"' => break,
+                    b'$' if self.get_next_char() == Some(b'{') => {
+                        self.position += 1;
+                        interpolation_flag = true;
+                        break;
+                    }
+                    b'\\' => {
+                        let bytes = self.bytes;
+                        let char_vec = decoded.get_or_insert_with(|| bytes[start..end].to_vec());
+                        self.consume_escape(char_vec);
+                    }
+                    _ => {
+                        if next_char == b'\n' {
+                            self.current_row += 1;
+                        }
+                        if let Some(char_vec) = decoded.as_mut() {
+                            char_vec.push(next_char);
+                        }
+                    }
                 }
-                char_vec.push(next_char);
+                end = self.position;
             } else {
+                let token_start = self.token_start;
+                self.push_error("unterminated string literal".to_string(), token_start);
                 break;
             }
         }
-        self.create_token_by_value(TokenType::String, char_vec)
+
+        let token_type = match (resumed, interpolation_flag) {
+            (false, false) => TokenType::String,
+            (false, true) => {
+                self.interpolation_depths.push(0);
+                TokenType::StringStart
+            }
+            (true, true) => TokenType::StringMiddle,
+            (true, false) => {
+                self.interpolation_depths.pop();
+                TokenType::StringEnd
+            }
+        };
+
+        let value = match decoded {
+            Some(char_vec) => Cow::Owned(String::from_utf8(char_vec).unwrap()),
+            None => Cow::Borrowed(&self.input[start..end]),
+        };
+        self.create_token_with_value(token_type, value)
     }
 
-    pub fn next_token(&mut self) -> Option<Token> {
-        let mut ret_val: Token = self.create_eof_token();
+    // decodes the escape sequence after a backslash and appends its utf-8 bytes
+    pub fn consume_escape(&mut self, char_vec: &mut Vec<u8>) {
+        let start = self.position - 1;
+        let decoded = match self.get_next_char() {
+            Some(byte) => {
+                self.position += 1;
+                match byte {
+                    b'n' => Some('\n'),
+                    b'r' => Some('\r'),
+                    b't' => Some('\t'),
+                    b'0' => Some('\0'),
+                    b'\\' => Some('\\'),
+                    b'"' => Some('"'),
+                    b'$' => Some('$'),
+                    b'x' => match self.consume_hex_escape() {
+                        Some(value) if value > 0x7F => {
+                            let message = format!(
+                                "escape sequence {} is not ascii. strings are utf-8, so write \\u{{{:X}}} for the character",
+                                &self.input[start..self.position],
+                                value
+                            );
+                            self.push_error(message, start);
+                            return;
+                        }
+                        value => value.map(|value| value as char),
+                    },
+                    b'u' => self.consume_unicode_escape(),
+                    _ => {
+                        // skip the rest of a multi-byte character
+                        while let Some(0x80...0xBF) = self.get_next_char() {
+                            self.position += 1;
+                        }
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        match decoded {
+            Some(decoded) => {
+                let mut buffer = [0; 4];
+                char_vec.extend_from_slice(decoded.encode_utf8(&mut buffer).as_bytes());
+            }
+            None => {
+                let message = format!(
+                    "invalid escape sequence {} in string literal",
+                    &self.input[start..self.position]
+                );
+                self.push_error(message, start);
+            }
+        }
+    }
+
+    // the byte of \xNN. only ascii(00-7F) is accepted, because strings are utf-8 from the lexer
+    // to codegen_string, and a single byte from 80 to FF is not a utf-8 character
+    pub fn consume_hex_escape(&mut self) -> Option<u8> {
+        let end = self.position + 2;
+        if end > self.bytes.len() {
+            self.position = self.bytes.len();
+            return None;
+        }
+        let digits = String::from_utf8_lossy(&self.bytes[self.position..end]).into_owned();
+        if digits.chars().all(|c| c.is_digit(16)) == false {
+            return None;
+        }
+        self.position = end;
+        u8::from_str_radix(&digits, 16).ok()
+    }
+
+    // \u{1F600}, up to six hex digits
+    pub fn consume_unicode_escape(&mut self) -> Option<char> {
+        if self.get_next_char() != Some(b'{') {
+            return None;
+        }
+        self.position += 1;
+
+        let mut digits = String::new();
+        while let Some(byte) = self.get_next_char() {
+            if byte == b'}' || byte == b'"' {
+                break;
+            }
+            self.position += 1;
+            digits.push(byte as char);
+        }
+        if self.get_next_char() != Some(b'}') {
+            return None;
+        }
+        self.position += 1;
+
+        if digits.len() == 0 || digits.len() > 6 {
+            return None;
+        }
+        match u32::from_str_radix(&digits, 16) {
+            Ok(value) => ::std::char::from_u32(value),
+            Err(_) => None,
+        }
+    }
+
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        let mut ret_val: Token<'a> = self.create_eof_token();
         loop {
             if let Some(byte) = self.get_next_char() {
+                self.token_start = self.position;
                 self.position += 1;
                 let flag = match byte {
                     b'0'...b'9' => {
-                        ret_val = self.consumue_character(byte, true);
+                        ret_val = self.consume_number(byte);
                         true
                     }
-                    b'a'...b'z' | b'A'...b'Z' => {
-                        ret_val = self.consumue_character(byte, false);
+                    b'a'...b'z' | b'A'...b'Z' | b'_' => {
+                        ret_val = self.consumue_character();
                         true
                     }
                     b'"' => {
-                        ret_val = self.consume_string();
+                        ret_val = self.consume_string(false);
                         true
                     }
                     b'/' => {
@@ -212,35 +582,46 @@ impl<'a> Lexer<'a> {
                         true
                     }
                     b',' => {
-                        ret_val = self.create_token_by_value(TokenType::Comma, vec![byte]);
+                        ret_val = self.create_token(TokenType::Comma);
                         true
                     }
                     b'.' => {
-                        ret_val = self.create_token_by_value(TokenType::Period, vec![byte]);
+                        ret_val = self.consume_period();
                         true
                     }
                     b'{' => {
-                        ret_val = self.create_token_by_value(TokenType::Lbrace, vec![byte]);
+                        if let Some(depth) = self.interpolation_depths.last_mut() {
+                            *depth += 1;
+                        }
+                        ret_val = self.create_token(TokenType::Lbrace);
                         true
                     }
                     b'}' => {
-                        ret_val = self.create_token_by_value(TokenType::Rbrace, vec![byte]);
+                        ret_val = match self.interpolation_depths.last_mut() {
+                            // closes `${`, so the string continues
+                            Some(0) => self.consume_string(true),
+                            Some(depth) => {
+                                *depth -= 1;
+                                self.create_token(TokenType::Rbrace)
+                            }
+                            None => self.create_token(TokenType::Rbrace),
+                        };
                         true
                     }
                     b'(' => {
-                        ret_val = self.create_token_by_value(TokenType::Lparen, vec![byte]);
+                        ret_val = self.create_token(TokenType::Lparen);
                         true
                     }
                     b')' => {
-                        ret_val = self.create_token_by_value(TokenType::Rparen, vec![byte]);
+                        ret_val = self.create_token(TokenType::Rparen);
                         true
                     }
                     b'[' => {
-                        ret_val = self.create_token_by_value(TokenType::Lbracket, vec![byte]);
+                        ret_val = self.create_token(TokenType::Lbracket);
                         true
                     }
                     b']' => {
-                        ret_val = self.create_token_by_value(TokenType::Rbracket, vec![byte]);
+                        ret_val = self.create_token(TokenType::Rbracket);
                         true
                     }
                     b'!' => {
@@ -248,19 +629,19 @@ impl<'a> Lexer<'a> {
                         true
                     }
                     b'*' => {
-                        ret_val = self.create_token_by_value(TokenType::Multiply, vec![byte]);
+                        ret_val = self.consume_asterisk();
                         true
                     }
                     b'%' => {
-                        ret_val = self.create_token_by_value(TokenType::Rem, vec![byte]);
+                        ret_val = self.create_token(TokenType::Rem);
                         true
                     }
                     b'+' => {
-                        ret_val = self.create_token_by_value(TokenType::Plus, vec![byte]);
+                        ret_val = self.create_token(TokenType::Plus);
                         true
                     }
                     b'-' => {
-                        ret_val = self.create_token_by_value(TokenType::Minus, vec![byte]);
+                        ret_val = self.create_token(TokenType::Minus);
                         true
                     }
                     b'<' => {
@@ -272,20 +653,32 @@ impl<'a> Lexer<'a> {
                         true
                     }
                     b':' => {
-                        ret_val = self.create_token_by_value(TokenType::Colon, vec![byte]);
+                        ret_val = self.create_token(TokenType::Colon);
                         true
                     }
                     b';' => {
-                        ret_val = self.create_token_by_value(TokenType::Semicolon, vec![byte]);
+                        ret_val = self.create_token(TokenType::Semicolon);
                         true
                     }
-                    b'\n' | b'\r' => {
+                    b'\n' => {
                         self.current_row += 1;
                         false
                     }
-                    b' ' => false,
+                    b' ' | b'\t' | b'\r' => false,
                     _ => {
-                        panic!("{} cannot be handled.", byte);
+                        // may be the first byte of a multi-byte utf-8 character
+                        self.position = self.token_start;
+                        let next_char = self.get_next_unicode_char().unwrap();
+                        self.position += next_char.len_utf8();
+
+                        if UnicodeXID::is_xid_start(next_char) {
+                            ret_val = self.consumue_character();
+                        } else {
+                            let start = self.token_start;
+                            self.push_error(format!("illegal character {:?}", next_char), start);
+                            ret_val = self.create_token(TokenType::Illegal);
+                        }
+                        true
                     }
                 };
 
@@ -304,7 +697,7 @@ impl<'a> Lexer<'a> {
 
 #[allow(dead_code)]
 fn lexer_assert(token: Token, token_type: TokenType, value: &str) {
-    let expected = Token::new(token_type, value.to_string(), 0);
+    let expected = Token::new(token_type, Cow::Borrowed(value), 0, 0);
     assert!(
         token == expected,
         "\r\nexpected: {:?} \r\nactual: {:?}",
@@ -324,16 +717,55 @@ fn digit() {
     lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "456");
 }
 
+#[test]
+fn float() {
+    let mut lexer = Lexer::new(
+        r#"
+    3.14 1e-3 2.5E+10 1.a
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Float, "3.14");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Float, "1e-3");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Float, "2.5E+10");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Period, ".");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "a");
+}
+
+#[test]
+fn integer_suffix() {
+    let mut lexer = Lexer::new(
+        r#"
+    10u8 300i64 1i8x 7 as u32
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "10u8");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "300i64");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "1i8x");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "7");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::As, "as");
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::LLVMTokenType(LLVMTokenType::U32),
+        "u32",
+    );
+}
+
 #[test]
 fn identifier() {
     let mut lexer = Lexer::new(
         r#"
-    123 abc 45d6
+    123 abc 45d6 to_float
     "#,
     );
     lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "123");
     lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "abc");
-    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "45d6");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "45d6");
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::Identifier,
+        "to_float",
+    );
 }
 
 #[test]
@@ -347,6 +779,77 @@ fn string() {
     lexer_assert(lexer.next_token().unwrap(), TokenType::String, "def");
 }
 
+#[test]
+fn string_escape() {
+    let mut lexer = Lexer::new(
+        r#"
+    "say \"hi\"\n" "a\tb\\c\0" "\x41\u{3042}\u{1F600}"
+    "#,
+    );
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::String,
+        "say \"hi\"\n",
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "a\tb\\c\0");
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::String,
+        "A\u{3042}\u{1F600}",
+    );
+    assert!(lexer.errors.is_empty());
+}
+
+#[test]
+fn string_interpolation() {
+    let mut lexer = Lexer::new(
+        r#"
+    "a${x}b${f({1})}c" "${"in${y}"}\${z}"
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringStart, "a");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "x");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringMiddle, "b");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "f");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Lparen, "(");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Lbrace, "{");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Rbrace, "}");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Rparen, ")");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringEnd, "c");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringStart, "");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringStart, "in");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "y");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringEnd, "");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::StringEnd, "${z}");
+    assert!(lexer.errors.is_empty());
+    assert!(lexer.interpolation_depths.is_empty());
+}
+
+#[test]
+fn invalid_string_escape() {
+    let mut lexer = Lexer::new(
+        r#"
+    "\q" "\x80" "\u{110000}" "\u{}" "open
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "");
+    lexer.next_token();
+    assert_eq!(
+        lexer.errors,
+        vec![
+            "invalid escape sequence \\q in string literal. row: 1, column: 5",
+            "escape sequence \\x80 is not ascii. strings are utf-8, so write \\u{80} for the character. row: 1, column: 10",
+            "invalid escape sequence \\u{110000} in string literal. row: 1, column: 17",
+            "invalid escape sequence \\u{} in string literal. row: 1, column: 30",
+            "unterminated string literal. row: 1, column: 36",
+        ]
+    );
+}
+
 #[test]
 fn array() {
     let mut lexer = Lexer::new(
@@ -374,6 +877,94 @@ fn comment() {
     lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "2");
 }
 
+#[test]
+fn line_and_nested_comment() {
+    let mut lexer = Lexer::new(
+        r#"
+    0 // 1 /* not a block
+    /* 2 /* 3 */
+       4 */ 5 / 6
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "0");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "5");
+    assert_eq!(lexer.current_row, 3);
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Divide, "/");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "6");
+
+    let comments: Vec<_> = lexer
+        .comments
+        .iter()
+        .map(|comment| (comment.kind, comment.value.as_ref(), comment.current_row))
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            (TokenType::Comment, "// 1 /* not a block", 1),
+            (TokenType::Comment, "/* 2 /* 3 */\n       4 */", 2),
+        ]
+    );
+}
+
+#[test]
+fn unterminated_comment() {
+    let mut lexer = Lexer::new("1 /* /* */ 2");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1");
+    lexer.next_token();
+    assert_eq!(
+        lexer.errors,
+        vec!["unterminated block comment. row: 0, column: 2"]
+    );
+}
+
+#[test]
+fn unicode_identifier_and_string() {
+    let mut lexer = Lexer::new(
+        r#"
+    let 名前 = "こんにちは🌏"; café_2
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Let, "let");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "名前");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Assign, "=");
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::String,
+        "こんにちは🌏",
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Semicolon, ";");
+
+    let token = lexer.next_token().unwrap();
+    lexer_assert(token.clone(), TokenType::Identifier, "café_2");
+    assert_eq!(token.current_column, 23);
+}
+
+#[test]
+fn illegal_character() {
+    let mut lexer = Lexer::new("\"あ\" 🌏 1");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::String, "あ");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "🌏");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1");
+    assert_eq!(
+        lexer.errors,
+        vec!["illegal character '🌏'. row: 0, column: 4"]
+    );
+}
+
+#[test]
+fn let_mut() {
+    let mut lexer = Lexer::new(
+        r#"
+    let mut abc = 1
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Let, "let");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Mut, "mut");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "abc");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Assign, "=");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1");
+}
+
 #[test]
 fn ban() {
     let mut lexer = Lexer::new(
@@ -402,6 +993,55 @@ fn division_multiple() {
     lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "3");
 }
 
+#[test]
+fn extern_ellipsis() {
+    let mut lexer = Lexer::new(
+        r#"
+    extern fn f(a: int, ...): int;
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Extern, "extern");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Fn, "fn");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "f");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Lparen, "(");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "a");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Colon, ":");
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::LLVMTokenType(LLVMTokenType::Integer),
+        "int",
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Comma, ",");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Ellipsis, "...");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Rparen, ")");
+}
+
+#[test]
+fn export() {
+    let mut lexer = Lexer::new(
+        r#"
+    export fn f(): int {}
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Export, "export");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Fn, "fn");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Identifier, "f");
+}
+
+#[test]
+fn power() {
+    let mut lexer = Lexer::new(
+        r#"
+    2 ** 3 * 4
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "2");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Power, "**");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "3");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Multiply, "*");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "4");
+}
+
 #[test]
 fn gt() {
     let mut lexer = Lexer::new(
@@ -431,7 +1071,7 @@ fn if_test() {
 fn llvm_token_test() {
     let mut lexer = Lexer::new(
         r#"
-    int string boolean null
+    int float string boolean null
     "#,
     );
     lexer_assert(
@@ -439,6 +1079,11 @@ fn llvm_token_test() {
         TokenType::LLVMTokenType(LLVMTokenType::Integer),
         "int",
     );
+    lexer_assert(
+        lexer.next_token().unwrap(),
+        TokenType::LLVMTokenType(LLVMTokenType::Float),
+        "float",
+    );
     lexer_assert(
         lexer.next_token().unwrap(),
         TokenType::LLVMTokenType(LLVMTokenType::String),
@@ -455,3 +1100,39 @@ fn llvm_token_test() {
         "null",
     );
 }
+
+#[test]
+fn radix_literal() {
+    let mut lexer = Lexer::new(
+        r#"
+    0xFF 0b1010 0o17 1_000_000 0xFFu8 1_000.5
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "0xFF");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "0b1010");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "0o17");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "1_000_000");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Digit, "0xFFu8");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Float, "1_000.5");
+    assert!(lexer.errors.is_empty());
+}
+
+#[test]
+fn malformed_number() {
+    let mut lexer = Lexer::new(
+        r#"
+    12abc 0b102 0x
+    "#,
+    );
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "12abc");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "0b102");
+    lexer_assert(lexer.next_token().unwrap(), TokenType::Illegal, "0x");
+    assert_eq!(
+        lexer.errors,
+        vec![
+            "invalid numeric literal 12abc. row: 1, column: 4",
+            "invalid numeric literal 0b102. row: 1, column: 10",
+            "invalid numeric literal 0x. row: 1, column: 16",
+        ]
+    );
+}
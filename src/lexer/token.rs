@@ -1,17 +1,26 @@
+use std::borrow::Cow;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum TokenType {
     LLVMTokenType(LLVMTokenType),
     Identifier,
     Digit,
+    Float,
+    Illegal,
+    Comment,
 
     Eof,
     Assign,
     Colon,
     Comma,
     Period,
+    Ellipsis, // ...
     Semicolon,
 
     String,
+    StringStart,  // "text${
+    StringMiddle, // }text${
+    StringEnd,    // }text"
 
     Lparen,   // (
     Rparen,   // )
@@ -28,9 +37,13 @@ pub enum TokenType {
     Else,   // else
     ElseIf, // elseif
     Let,    // let
+    As,     // as
+    Mut,    // mut
     Return, // return
     While,  // while
     Break,  // break
+    Extern, // extern
+    Export, // export
 
     // for Arithmetic
     Eq,       // =
@@ -43,6 +56,7 @@ pub enum TokenType {
     Minus,    // -
     Divide,   // /
     Multiply, // *
+    Power,    // **
     Rem,      // %
     Bang,     // !
 }
@@ -50,29 +64,40 @@ pub enum TokenType {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum LLVMTokenType {
     Integer,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Float,
     String,
     Boolean,
     Null,
 }
 
 #[derive(Clone, Debug)]
-pub struct Token {
+pub struct Token<'a> {
     pub kind: TokenType,
-    pub value: String,
+    pub value: Cow<'a, str>,
     pub current_row: usize,
+    pub current_column: usize,
 }
 
-impl Token {
-    pub fn new(kind: TokenType, value: String, row: usize) -> Token {
+impl<'a> Token<'a> {
+    pub fn new(kind: TokenType, value: Cow<'a, str>, row: usize, column: usize) -> Token<'a> {
         Token {
             kind: kind,
             value: value,
             current_row: row,
+            current_column: column,
         }
     }
 }
 
-impl PartialEq for Token {
+impl<'a> PartialEq for Token<'a> {
     fn eq(&self, other: &Token) -> bool {
         self.kind == other.kind && self.value == other.value
     }
@@ -7,9 +7,11 @@ extern crate clap;
 extern crate libc;
 extern crate llvm_sys;
 extern crate rustc_llvm_proxy;
+extern crate unicode_xid;
 
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 
 use clap::{App, Arg};
 
@@ -20,13 +22,23 @@ mod parser;
 use parser::parser::*;
 
 mod ir;
+use ir::optimize::*;
 
 mod evaluate_ir;
 use evaluate_ir::environment::*;
 use evaluate_ir::eval::*;
 
+mod output;
+use output::*;
+
 const INPUT_FILE: &'static str = "input_file";
 const OUTPUT_FILE: &'static str = "output_file";
+const CHECKED: &'static str = "checked";
+const EMIT: &'static str = "emit";
+const CRATE_TYPE: &'static str = "crate-type";
+const OPT_LEVEL: &'static str = "opt-level";
+const TARGET: &'static str = "target";
+const TARGET_CPU: &'static str = "target-cpu";
 
 fn read_file(file_name: &str) -> Result<String, String> {
     if let Ok(mut file) = File::open(file_name) {
@@ -46,10 +58,68 @@ fn main() {
         .author("rchaser53. <tayoshizawa29@gmail.com>")
         .arg(Arg::with_name(INPUT_FILE).index(1))
         .arg(Arg::with_name(OUTPUT_FILE).index(2))
+        .arg(
+            Arg::with_name(CHECKED)
+                .long(CHECKED)
+                .help("traps on out of bounds indexes, division by zero and integer overflow"),
+        )
+        .arg(
+            Arg::with_name(EMIT)
+                .long(EMIT)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .possible_values(&["llvm-ir", "bc", "asm", "obj", "exe", "header"])
+                .help("what to write next to the output file. accepts a list like llvm-ir,asm"),
+        )
+        .arg(
+            Arg::with_name(CRATE_TYPE)
+                .long(CRATE_TYPE)
+                .takes_value(true)
+                .possible_values(&["bin", "cdylib", "staticlib"])
+                .help("also links a lib<name>.so or lib<name>.a next to the output file"),
+        )
+        .arg(
+            Arg::with_name(OPT_LEVEL)
+                .short("O")
+                .takes_value(true)
+                .possible_values(&["0", "1", "2", "3", "s"])
+                .help("the optimization level. 0 is none and the default, 1 to 3 optimize more and more, s optimizes for size"),
+        )
+        .arg(
+            Arg::with_name(TARGET)
+                .long(TARGET)
+                .takes_value(true)
+                .help("the target triple like aarch64-unknown-linux-gnu. the default is the host"),
+        )
+        .arg(
+            Arg::with_name(TARGET_CPU)
+                .long(TARGET_CPU)
+                .takes_value(true)
+                .help("the cpu of the target like cortex-a53. native is the cpu of the host"),
+        )
         .get_matches();
 
     let input_file_name = matches.value_of(INPUT_FILE).unwrap_or("input.mr");
     let output_file_name = matches.value_of(OUTPUT_FILE).unwrap_or("output.ll");
+    let crate_type = CrateType::from_name(matches.value_of(CRATE_TYPE).unwrap_or("bin")).unwrap();
+    // a library is the output by itself unless something else is asked for
+    let mut emit_kinds: Vec<EmitKind> = Vec::new();
+    match matches.values_of(EMIT) {
+        // in the order they were asked for, each once
+        Some(names) => {
+            for kind in names.filter_map(EmitKind::from_name) {
+                if emit_kinds.contains(&kind) == false {
+                    emit_kinds.push(kind);
+                }
+            }
+        }
+        None if crate_type.is_library() => {}
+        None => emit_kinds.push(EmitKind::LLVMIR),
+    }
+    let opt_level =
+        OptimizationLevel::from_name(matches.value_of(OPT_LEVEL).unwrap_or("0")).unwrap();
     match read_file(input_file_name) {
         Ok(input) => {
             let mut lexer = Lexer::new(&input);
@@ -61,13 +131,36 @@ fn main() {
             }
 
             let mut eval = Eval::new();
+            eval.checked = matches.is_present(CHECKED);
+            eval.file_name = input_file_name.to_string();
             eval.entry_eval_program(program, &mut Environment::new());
             if eval.has_error() {
                 panic!("{}", eval.emit_error());
             }
 
             eval.dump_llvm();
-            eval.emit_llvm(output_file_name);
+            let output_path = Path::new(output_file_name);
+            let machine = match create_machine(
+                &eval.lc,
+                matches.value_of(TARGET),
+                matches.value_of(TARGET_CPU),
+                opt_level,
+            ) {
+                Ok(machine) => machine,
+                Err(err) => panic!("{}", err),
+            };
+            optimize_module(eval.lc.module, opt_level);
+            for kind in emit_kinds {
+                if let Err(err) = emit(&eval, machine, kind, output_path) {
+                    panic!("{}", err);
+                }
+            }
+            // comes after the emits, because a library hides main
+            if let Some(path) = library_path(output_path, crate_type) {
+                if let Err(err) = build_library(&eval, machine, crate_type, &path) {
+                    panic!("{}", err);
+                }
+            }
         }
         Err(error) => {
             panic!("{}", error);
@@ -0,0 +1,605 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use llvm_sys::target_machine::*;
+use llvm_sys::LLVMLinkage;
+
+use ir::creator::*;
+use ir::optimize::*;
+use ir::scope::*;
+use ir::target::*;
+
+use evaluate_ir::eval::*;
+use evaluate_ir::header::*;
+
+#[allow(unused_imports)]
+use evaluate_ir::environment::*;
+
+#[allow(unused_imports)]
+use lexer::lexer::*;
+
+#[allow(unused_imports)]
+use parser::parser::*;
+
+// what `--emit` writes
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum EmitKind {
+    LLVMIR,
+    Header,
+    Bitcode,
+    Assembly,
+    Object,
+    Executable,
+}
+
+impl EmitKind {
+    pub fn from_name(name: &str) -> Option<EmitKind> {
+        match name {
+            "llvm-ir" => Some(EmitKind::LLVMIR),
+            "header" => Some(EmitKind::Header),
+            "bc" => Some(EmitKind::Bitcode),
+            "asm" => Some(EmitKind::Assembly),
+            "obj" => Some(EmitKind::Object),
+            "exe" => Some(EmitKind::Executable),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmitKind::LLVMIR => "ll",
+            EmitKind::Header => "h",
+            EmitKind::Bitcode => "bc",
+            EmitKind::Assembly => "s",
+            EmitKind::Object => "o",
+            EmitKind::Executable => "",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum CrateType {
+    Bin,
+    Cdylib,
+    Staticlib,
+}
+
+impl CrateType {
+    pub fn from_name(name: &str) -> Option<CrateType> {
+        match name {
+            "bin" => Some(CrateType::Bin),
+            "cdylib" => Some(CrateType::Cdylib),
+            "staticlib" => Some(CrateType::Staticlib),
+            _ => None,
+        }
+    }
+
+    pub fn is_library(&self) -> bool {
+        *self != CrateType::Bin
+    }
+}
+
+// `add.ll` is used as it is for llvm-ir, and gives `add.h`, `add.s`, `add` and so on for the others
+pub fn emit_path(output: &Path, kind: EmitKind) -> PathBuf {
+    match kind {
+        EmitKind::LLVMIR => output.to_path_buf(),
+        _ => output.with_extension(kind.extension()),
+    }
+}
+
+pub fn emit(
+    eval: &Eval,
+    machine: LLVMTargetMachineRef,
+    kind: EmitKind,
+    output: &Path,
+) -> Result<(), String> {
+    let path = emit_path(output, kind);
+    match kind {
+        EmitKind::LLVMIR => eval.lc.emit_file(&path),
+        EmitKind::Header => {
+            let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+            fs::write(&path, build_header(&stem, &eval.exports))
+                .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+        }
+        EmitKind::Bitcode => eval.lc.emit_bitcode(&path),
+        EmitKind::Assembly => emit_to_file(
+            machine,
+            eval.lc.module,
+            &path,
+            LLVMCodeGenFileType::LLVMAssemblyFile,
+        ),
+        EmitKind::Object => emit_to_file(
+            machine,
+            eval.lc.module,
+            &path,
+            LLVMCodeGenFileType::LLVMObjectFile,
+        ),
+        EmitKind::Executable => build_executable(&eval.lc, machine, &path),
+    }
+}
+
+// `out/add.ll` gives `out/libadd.so` or `out/libadd.a`
+pub fn library_path(output: &Path, crate_type: CrateType) -> Option<PathBuf> {
+    let extension = match crate_type {
+        CrateType::Bin => return None,
+        CrateType::Cdylib => "so",
+        CrateType::Staticlib => "a",
+    };
+    let stem = output.file_stem()?.to_string_lossy().into_owned();
+    Some(output.with_file_name(format!("lib{}.{}", stem, extension)))
+}
+
+// a machine for the target triple, or for the host without one. `native` as the cpu is the cpu
+// of the host. the module takes the triple and the data layout, so optimize_module has to run
+// after this
+pub fn create_machine(
+    lc: &LLVMCreator,
+    target: Option<&str>,
+    cpu: Option<&str>,
+    opt_level: OptimizationLevel,
+) -> Result<LLVMTargetMachineRef, String> {
+    let triple = match target {
+        Some(triple) => {
+            initialize_all_targets();
+            normalize_target_triple(triple)
+        }
+        None => {
+            initialize_native_target()?;
+            default_target_triple()
+        }
+    };
+    let cpu = match cpu {
+        Some("native") => host_cpu_name(),
+        Some(cpu) => cpu.to_string(),
+        None => String::new(),
+    };
+    let machine = create_target_machine(&triple, &cpu, opt_level.code_gen_level())?;
+    if let Err(err) = check_runtime_target(lc, machine) {
+        dispose_target_machine(machine);
+        return Err(err);
+    }
+    set_module_target(lc.module, machine);
+    Ok(machine)
+}
+
+// libc as the runtime declares it, with a 64 bit size_t, a `struct timespec` of two i64s, the
+// clock ids of linux and the `stdin` and `stderr` globals of glibc
+const LINUX_64_BUILT_INS: [&str; 10] = [
+    "snprintf",
+    "memcmp",
+    "strlen",
+    "malloc",
+    "memcpy",
+    "realloc",
+    "fwrite",
+    "clock_gettime",
+    "stdin",
+    "stderr",
+];
+
+// a program which uses none of LINUX_64_BUILT_INS fits any target
+fn check_runtime_target(lc: &LLVMCreator, machine: LLVMTargetMachineRef) -> Result<(), String> {
+    let triple = get_target_machine_triple(machine);
+    let is_linux = triple.split('-').any(|part| part == "linux");
+    if get_target_pointer_size(machine) == 8 && is_linux {
+        return Ok(());
+    }
+    for name in LINUX_64_BUILT_INS.iter() {
+        if has_uses(lc.built_ins[name]) {
+            return Err(format!(
+                "the runtime uses {} of 64 bit linux, so the program cannot be built for {}",
+                name, triple
+            ));
+        }
+    }
+    Ok(())
+}
+
+// `cc` and `ar` of the host only link objects for the host
+fn check_host_target(machine: LLVMTargetMachineRef) -> Result<(), String> {
+    let host = normalize_target_triple(&default_target_triple());
+    let target = get_target_machine_triple(machine);
+    if normalize_target_triple(&target) != host {
+        return Err(format!(
+            "only objects for {} can be linked, but the target is {}. use --emit=obj and a linker for the target",
+            host, target
+        ));
+    }
+    Ok(())
+}
+
+fn emit_object(lc: &LLVMCreator, machine: LLVMTargetMachineRef, path: &Path) -> Result<(), String> {
+    emit_to_file(
+        machine,
+        lc.module,
+        path,
+        LLVMCodeGenFileType::LLVMObjectFile,
+    )
+}
+
+// links the module into a shared library with `cc`, or archives it with `ar`.
+// only the `export fn` functions are exported. the top level code stays in the library as an
+// internal `main`, which nothing calls
+pub fn build_library(
+    eval: &Eval,
+    machine: LLVMTargetMachineRef,
+    crate_type: CrateType,
+    path: &Path,
+) -> Result<(), String> {
+    check_host_target(machine)?;
+    for definition in get_definitions(eval.lc.module) {
+        let name = get_value_name(definition);
+        let exported = eval.exports.iter().any(|function| function.name == name);
+        if exported == false && get_linkage(definition) != LLVMLinkage::LLVMPrivateLinkage {
+            set_linkage(definition, LLVMLinkage::LLVMInternalLinkage);
+        }
+    }
+
+    let object = temporary_object(path);
+    emit_object(&eval.lc, machine, &object)?;
+    let result = match crate_type {
+        CrateType::Cdylib => run(Command::new("cc")
+            .arg("-shared")
+            .arg("-o")
+            .arg(path)
+            .arg(&object)),
+        CrateType::Staticlib => {
+            // `ar` would add to an old archive
+            let _ = fs::remove_file(path);
+            run(Command::new("ar").arg("rcs").arg(path).arg(&object))
+        }
+        CrateType::Bin => unreachable!("a binary is not a library"),
+    };
+    let _ = fs::remove_file(&object);
+    result
+}
+
+// links the module with libc and libm by `cc`
+pub fn build_executable(
+    lc: &LLVMCreator,
+    machine: LLVMTargetMachineRef,
+    path: &Path,
+) -> Result<(), String> {
+    check_host_target(machine)?;
+    let object = temporary_object(path);
+    emit_object(lc, machine, &object)?;
+    let result = run(Command::new("cc")
+        .arg(&object)
+        .arg("-o")
+        .arg(path)
+        .arg("-lm"));
+    let _ = fs::remove_file(&object);
+    result
+}
+
+// a number for names which have to be unique in this process
+fn unique_id() -> usize {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_ID.fetch_add(1, Ordering::SeqCst)
+}
+
+// an object which only lives until it is linked
+fn temporary_object(path: &Path) -> PathBuf {
+    env::temp_dir().join(format!(
+        "{}-{}-{}.o",
+        path.file_stem().unwrap_or_default().to_string_lossy(),
+        process::id(),
+        unique_id()
+    ))
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let status = command
+        .status()
+        .map_err(|err| format!("failed to run {:?}: {}", command, err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{:?} failed with {}", command, status))
+    }
+}
+
+#[allow(dead_code)]
+fn compile_input(input: &str) -> Eval {
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+    let mut eval = Eval::new();
+    eval.entry_eval_program(program, &mut Environment::new());
+    if eval.has_error() {
+        panic!("{}", eval.emit_error());
+    }
+    eval
+}
+
+#[allow(dead_code)]
+fn compile_library(input: &str, crate_type: CrateType, path: &Path) -> Eval {
+    let eval = compile_input(input);
+    let machine = create_machine(&eval.lc, None, None, OptimizationLevel::O0).unwrap();
+    build_library(&eval, machine, crate_type, path).unwrap();
+    dispose_target_machine(machine);
+    eval
+}
+
+#[allow(dead_code)]
+fn test_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!(
+        "rust-monkey-ir-{}-{}-{}",
+        name,
+        process::id(),
+        unique_id()
+    ));
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+// links the program into an executable for the host and runs it, so that tests can check
+// what it prints and how it exits
+#[allow(dead_code)]
+pub fn run_test_executable(
+    eval: &Eval,
+    opt_level: OptimizationLevel,
+    arguments: &[&str],
+) -> process::Output {
+    let directory = test_directory("run");
+    let path = directory.join("program");
+    let machine = create_machine(&eval.lc, None, None, opt_level).unwrap();
+    optimize_module(eval.lc.module, opt_level);
+    build_executable(&eval.lc, machine, &path).unwrap();
+    dispose_target_machine(machine);
+    let output = Command::new(&path).args(arguments).output().unwrap();
+    let _ = fs::remove_dir_all(&directory);
+    output
+}
+
+#[test]
+fn output_paths() {
+    assert_eq!(EmitKind::from_name("header"), Some(EmitKind::Header));
+    assert_eq!(CrateType::from_name("dylib"), None);
+    let output = Path::new("out/add.ll");
+    assert_eq!(
+        emit_path(output, EmitKind::LLVMIR),
+        PathBuf::from("out/add.ll")
+    );
+    assert_eq!(
+        emit_path(output, EmitKind::Object),
+        PathBuf::from("out/add.o")
+    );
+    assert_eq!(
+        emit_path(output, EmitKind::Executable),
+        PathBuf::from("out/add")
+    );
+    assert_eq!(library_path(output, CrateType::Bin), None);
+    assert_eq!(
+        library_path(output, CrateType::Cdylib),
+        Some(PathBuf::from("out/libadd.so"))
+    );
+    assert_eq!(
+        library_path(output, CrateType::Staticlib),
+        Some(PathBuf::from("out/libadd.a"))
+    );
+}
+
+#[test]
+fn shared_library() {
+    use std::ffi::CString;
+
+    let directory = test_directory("cdylib");
+    let path = directory.join("libadd.so");
+    compile_library(
+        r#"
+    let twice = fn(a: int): int { return a * 2; };
+    export fn add(a: int, b: int): int { return twice(a) + b + argc() + len(argv(1)); };
+"#,
+        CrateType::Cdylib,
+        &path,
+    );
+
+    let add: extern "C" fn(i32, i32) -> i32 = unsafe {
+        let path = c_string!(path.to_str().unwrap());
+        let library = ::libc::dlopen(path.as_ptr(), ::libc::RTLD_NOW);
+        assert!(library.is_null() == false);
+        // the top level code and the runtime helpers stay inside the library
+        for name in vec!["main", "__mr_rt.argc", "__mr_rt.argv"] {
+            assert!(::libc::dlsym(library, c_string!(name).as_ptr()).is_null());
+        }
+        let symbol = ::libc::dlsym(library, c_string!("add").as_ptr());
+        assert!(symbol.is_null() == false);
+        ::std::mem::transmute(symbol)
+    };
+    // main never runs in a library, so there are no arguments
+    assert_eq!(add(20, 2), 42);
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn static_library_with_header() {
+    let directory = test_directory("staticlib");
+    let eval = compile_library(
+        r#"
+    export fn scale(x: float, twice: boolean): float {
+      let mut result = x;
+      if (twice) { result = x * 2.0; }
+      return result;
+    };
+    return 1;
+"#,
+        CrateType::Staticlib,
+        &directory.join("libscale.a"),
+    );
+    fs::write(
+        directory.join("scale.h"),
+        build_header("scale", &eval.exports),
+    )
+    .unwrap();
+    // a C program with its own main links against the library through the header
+    fs::write(
+        directory.join("main.c"),
+        "#include \"scale.h\"\nint main(void) { return (int)scale(10.5, true); }\n",
+    )
+    .unwrap();
+
+    let program = directory.join("main");
+    run(Command::new("cc")
+        .arg(directory.join("main.c"))
+        .arg(directory.join("libscale.a"))
+        .arg("-o")
+        .arg(&program))
+    .unwrap();
+    let status = Command::new(&program).status().unwrap();
+    assert_eq!(status.code(), Some(21));
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn executable() {
+    let directory = test_directory("exe");
+    let output = directory.join("power.ll");
+    let eval = compile_input(
+        r#"
+    let base = 2.0;
+    println("cubed");
+    return (base ** 3.0) as int + len(argv(1));
+"#,
+    );
+    let machine = create_machine(&eval.lc, None, None, OptimizationLevel::O0).unwrap();
+    emit(&eval, machine, EmitKind::Object, &output).unwrap();
+    let object = fs::read(directory.join("power.o")).unwrap();
+    assert_eq!(&object[..4], b"\x7fELF");
+
+    emit(&eval, machine, EmitKind::Executable, &output).unwrap();
+    dispose_target_machine(machine);
+    let result = Command::new(directory.join("power"))
+        .arg("four")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&result.stdout), "cubed\n");
+    assert_eq!(result.status.code(), Some(12));
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn bitcode_and_assembly() {
+    let directory = test_directory("bc-asm");
+    let output = directory.join("add.ll");
+    let eval = compile_input("export fn add(a: int, b: int): int { return a + b; };");
+    let machine = create_machine(&eval.lc, None, None, OptimizationLevel::O0).unwrap();
+    for kind in [EmitKind::LLVMIR, EmitKind::Bitcode, EmitKind::Assembly].iter() {
+        emit(&eval, machine, *kind, &output).unwrap();
+    }
+    dispose_target_machine(machine);
+
+    // every kind is emitted from the same module, so the triple is in the ir as well
+    let ir = fs::read_to_string(directory.join("add.ll")).unwrap();
+    assert!(ir.contains(&format!("target triple = \"{}\"", default_target_triple())));
+    let bitcode = fs::read(directory.join("add.bc")).unwrap();
+    assert_eq!(&bitcode[..4], b"BC\xc0\xde");
+    let assembly = fs::read_to_string(directory.join("add.s")).unwrap();
+    assert!(assembly.contains("add:"));
+
+    let missing = directory.join("missing").join("add.ll");
+    assert!(eval.lc.emit_file(&missing).is_err());
+    let _ = fs::remove_dir_all(&directory);
+}
+
+// the object for the target, which fails the test when LLVM is built without its backend
+#[allow(dead_code)]
+fn emit_cross_compiled_object(input: &str, target: &str, triple: &str) -> Vec<u8> {
+    initialize_all_targets();
+    assert!(has_target(triple), "LLVM has no backend for {}", triple);
+    let directory = test_directory("cross");
+    let output = directory.join(format!("{}.ll", triple));
+    let eval = compile_input(input);
+    let machine = create_machine(&eval.lc, Some(target), None, OptimizationLevel::O2).unwrap();
+    optimize_module(eval.lc.module, OptimizationLevel::O2);
+    assert_eq!(get_module_target(eval.lc.module), triple);
+    emit(&eval, machine, EmitKind::LLVMIR, &output).unwrap();
+    emit(&eval, machine, EmitKind::Assembly, &output).unwrap();
+    emit(&eval, machine, EmitKind::Object, &output).unwrap();
+    let error = build_executable(&eval.lc, machine, &directory.join(triple)).unwrap_err();
+    assert!(error.contains(triple), "{}", error);
+    dispose_target_machine(machine);
+
+    let ir = fs::read_to_string(&output).unwrap();
+    assert!(ir.contains(&format!("target triple = \"{}\"", triple)));
+    let object = fs::read(output.with_extension("o")).unwrap();
+    let _ = fs::remove_dir_all(&directory);
+    object
+}
+
+// e_machine of the ELF header is at 18
+#[test]
+fn cross_compiled_aarch64() {
+    let object = emit_cross_compiled_object(
+        r#"
+    export fn add(a: int, b: int): int { return a + b; };
+    printf("${add(1, 2)}\n");
+"#,
+        "aarch64-linux-gnu",
+        "aarch64-unknown-linux-gnu",
+    );
+    assert_eq!(&object[..4], b"\x7fELF");
+    assert_eq!(&object[18..20], &[0xb7, 0]);
+
+    let eval = compile_input("return 0;");
+    let machine = create_machine(
+        &eval.lc,
+        Some("aarch64-linux-gnu"),
+        Some("cortex-a53"),
+        OptimizationLevel::O0,
+    )
+    .unwrap();
+    assert_eq!(get_target_machine_cpu(machine), "cortex-a53");
+    dispose_target_machine(machine);
+}
+
+#[test]
+fn cross_compiled_riscv64() {
+    let object = emit_cross_compiled_object(
+        "export fn add(a: int, b: int): int { return a + b; };",
+        "riscv64-unknown-linux-gnu",
+        "riscv64-unknown-linux-gnu",
+    );
+    assert_eq!(&object[..4], b"\x7fELF");
+    assert_eq!(&object[18..20], &[0xf3, 0]);
+}
+
+#[test]
+fn cross_compiled_wasm32() {
+    let object = emit_cross_compiled_object(
+        "export fn add(a: int, b: int): int { return a + b; };",
+        "wasm32-unknown-unknown",
+        "wasm32-unknown-unknown",
+    );
+    assert_eq!(&object[..4], b"\0asm");
+
+    // strings need malloc and snprintf with the types of 64 bit linux
+    let eval = compile_input(r#"let s = "a" + "b"; return len(s);"#);
+    let error = create_machine(
+        &eval.lc,
+        Some("wasm32-unknown-unknown"),
+        None,
+        OptimizationLevel::O0,
+    );
+    assert_eq!(
+        error.err(),
+        Some(
+            "the runtime uses malloc of 64 bit linux, so the program cannot be built for wasm32-unknown-unknown"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn unknown_target() {
+    let eval = compile_input("return 0;");
+    let error = create_machine(
+        &eval.lc,
+        Some("nonsense-triple"),
+        None,
+        OptimizationLevel::O0,
+    );
+    assert!(error.is_err());
+}
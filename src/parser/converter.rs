@@ -5,17 +5,24 @@ use parser::infix::*;
 
 pub fn get_expression_llvm_type(expression: &Expression) -> LLVMExpressionType {
     match expression.clone() {
-        Expression::IntegerLiteral(_, _) => LLVMExpressionType::Integer,
-        Expression::StringLiteral(string, _) => LLVMExpressionType::String(string.len() as u32),
+        Expression::IntegerLiteral(_, integer_type, _) => {
+            LLVMExpressionType::Integer(integer_type.unwrap_or(IntegerType::I32))
+        }
+        Expression::FloatLiteral(_, _) => LLVMExpressionType::Float,
+        Expression::StringLiteral(_, _) => LLVMExpressionType::String,
+        Expression::Interpolation(_, _, _) => LLVMExpressionType::String,
         Expression::Boolean(_, _) => LLVMExpressionType::Boolean,
         Expression::Array(expression_type, elements) => {
             LLVMExpressionType::Array(Box::new(expression_type), elements.len() as u32)
         }
         Expression::ArrayElement(_, boxed_element, _) => get_expression_llvm_type(&boxed_element),
+        Expression::Slice(_, _, _, _) => LLVMExpressionType::String,
         Expression::Infix(infix, left, _, _) => handle_infix_type(infix, *left),
+        Expression::Cast(_, expression_type, _) => expression_type,
         Expression::Function {
             parameters: _,
             parameter_types: _,
+            parameter_mutabilities: _,
             body: _,
             return_type: _,
             location: _,
@@ -28,10 +35,15 @@ pub fn get_expression_llvm_type(expression: &Expression) -> LLVMExpressionType {
 pub fn handle_infix_type(infix: Infix, left: Expression) -> LLVMExpressionType {
     match infix {
         Infix::Plus => get_expression_llvm_type(&left),
-        Infix::Minus => LLVMExpressionType::Integer,
-        Infix::Divide => LLVMExpressionType::Integer,
-        Infix::Multiply => LLVMExpressionType::Integer,
-        Infix::Rem => LLVMExpressionType::Integer,
+        Infix::Minus | Infix::Divide | Infix::Multiply | Infix::Power | Infix::Rem => {
+            match get_expression_llvm_type(&left) {
+                LLVMExpressionType::Float => LLVMExpressionType::Float,
+                LLVMExpressionType::Integer(integer_type) => {
+                    LLVMExpressionType::Integer(integer_type)
+                }
+                _ => LLVMExpressionType::Integer(IntegerType::I32),
+            }
+        }
         Infix::Eq => LLVMExpressionType::Boolean,
         Infix::NotEq => LLVMExpressionType::Boolean,
         Infix::Gte => LLVMExpressionType::Boolean,
@@ -41,16 +53,21 @@ pub fn handle_infix_type(infix: Infix, left: Expression) -> LLVMExpressionType {
     }
 }
 
-pub fn convert_token_to_expression_type(token: Token) -> LLVMExpressionType {
+pub fn convert_token_to_expression_type(token: &Token) -> LLVMExpressionType {
     match token.kind {
         TokenType::LLVMTokenType(llvm_type) => match llvm_type {
             LLVMTokenType::Boolean => LLVMExpressionType::Boolean,
-            LLVMTokenType::Integer => LLVMExpressionType::Integer,
-            LLVMTokenType::String => {
-                // need to include null character(+1)
-                let string_length = (token.value.len() + 1) as u32;
-                LLVMExpressionType::String(string_length)
-            }
+            LLVMTokenType::Integer => LLVMExpressionType::Integer(IntegerType::I32),
+            LLVMTokenType::I8 => LLVMExpressionType::Integer(IntegerType::I8),
+            LLVMTokenType::I16 => LLVMExpressionType::Integer(IntegerType::I16),
+            LLVMTokenType::I32 => LLVMExpressionType::Integer(IntegerType::I32),
+            LLVMTokenType::I64 => LLVMExpressionType::Integer(IntegerType::I64),
+            LLVMTokenType::U8 => LLVMExpressionType::Integer(IntegerType::U8),
+            LLVMTokenType::U16 => LLVMExpressionType::Integer(IntegerType::U16),
+            LLVMTokenType::U32 => LLVMExpressionType::Integer(IntegerType::U32),
+            LLVMTokenType::U64 => LLVMExpressionType::Integer(IntegerType::U64),
+            LLVMTokenType::Float => LLVMExpressionType::Float,
+            LLVMTokenType::String => LLVMExpressionType::String,
             LLVMTokenType::Null => LLVMExpressionType::Null,
         },
         _ => LLVMExpressionType::Null,
@@ -1,22 +1,48 @@
 use std::fmt;
+use std::rc::Rc;
 
 use parser::infix::*;
 use parser::prefix::*;
 use parser::statements::*;
 
 #[derive(PartialEq, Clone, Debug)]
-pub struct Identifier(pub String);
+pub struct Identifier(pub Rc<str>);
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Mutability {
+    Immutable,
+    Mutable,
+}
+
+impl fmt::Display for Mutability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mutability::Immutable => write!(f, ""),
+            Mutability::Mutable => write!(f, "mut "),
+        }
+    }
+}
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Expression {
     Identifier(Identifier, Location),
-    IntegerLiteral(u64, Location),
+    IntegerLiteral(u64, Option<IntegerType>, Location),
+    FloatLiteral(f64, Location),
     StringLiteral(String, Location),
+    // texts surround the interpolated expressions, so texts.len() == expressions.len() + 1
+    Interpolation(Vec<String>, Vec<Expression>, Location),
     Boolean(bool, Location),
     Array(LLVMExpressionType, Vec<Expression>),
     ArrayElement(Identifier, Box<Expression>, Location),
+    Slice(
+        Identifier,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+        Location,
+    ),
     Prefix(Prefix, Box<Expression>, Location),
     Infix(Infix, Box<Expression>, Box<Expression>, Location),
+    Cast(Box<Expression>, LLVMExpressionType, Location),
     If {
         conditions: Vec<Expression>,
         bodies: Vec<BlockStatement>,
@@ -25,6 +51,7 @@ pub enum Expression {
     Function {
         parameters: Vec<Identifier>,
         parameter_types: Vec<LLVMExpressionType>,
+        parameter_mutabilities: Vec<Mutability>,
         body: BlockStatement,
         return_type: LLVMExpressionType,
         location: Location,
@@ -42,18 +69,103 @@ pub struct Call {
 #[derive(PartialEq, Clone, Debug)]
 pub struct Location {
     pub row: usize,
+    pub column: usize,
 }
 
 impl Location {
-    pub fn new(row: usize) -> Self {
-        Location { row: row }
+    pub fn new(row: usize, column: usize) -> Self {
+        Location {
+            row: row,
+            column: column,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum IntegerType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl IntegerType {
+    pub fn from_suffix(suffix: &str) -> Option<IntegerType> {
+        match suffix {
+            "i8" => Some(IntegerType::I8),
+            "i16" => Some(IntegerType::I16),
+            "i32" => Some(IntegerType::I32),
+            "i64" => Some(IntegerType::I64),
+            "u8" => Some(IntegerType::U8),
+            "u16" => Some(IntegerType::U16),
+            "u32" => Some(IntegerType::U32),
+            "u64" => Some(IntegerType::U64),
+            _ => None,
+        }
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        match self {
+            IntegerType::I8 | IntegerType::U8 => 8,
+            IntegerType::I16 | IntegerType::U16 => 16,
+            IntegerType::I32 | IntegerType::U32 => 32,
+            IntegerType::I64 | IntegerType::U64 => 64,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        match self {
+            IntegerType::I8 | IntegerType::I16 | IntegerType::I32 | IntegerType::I64 => true,
+            _ => false,
+        }
+    }
+
+    pub fn max_value(&self) -> u64 {
+        if self.is_signed() {
+            (1u64 << (self.bit_width() - 1)) - 1
+        } else if self.bit_width() == 64 {
+            u64::max_value()
+        } else {
+            (1u64 << self.bit_width()) - 1
+        }
+    }
+
+    // the magnitude of a negative signed literal may be one more than max_value
+    pub fn fits(&self, value: u64, negative: bool) -> bool {
+        if negative == false {
+            value <= self.max_value()
+        } else if self.is_signed() {
+            value <= self.max_value() + 1
+        } else {
+            value == 0
+        }
+    }
+}
+
+impl fmt::Display for IntegerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntegerType::I8 => write!(f, "i8"),
+            IntegerType::I16 => write!(f, "i16"),
+            IntegerType::I32 => write!(f, "i32"),
+            IntegerType::I64 => write!(f, "i64"),
+            IntegerType::U8 => write!(f, "u8"),
+            IntegerType::U16 => write!(f, "u16"),
+            IntegerType::U32 => write!(f, "u32"),
+            IntegerType::U64 => write!(f, "u64"),
+        }
     }
 }
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum LLVMExpressionType {
-    Integer,
-    String(u32),
+    Integer(IntegerType),
+    Float,
+    String,
     Boolean,
     Null,
     Array(Box<LLVMExpressionType>, u32),
@@ -64,8 +176,10 @@ pub enum LLVMExpressionType {
 impl fmt::Display for LLVMExpressionType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            LLVMExpressionType::Integer => write!(f, "{}", "int"),
-            LLVMExpressionType::String(_) => write!(f, "{}", "string"),
+            LLVMExpressionType::Integer(IntegerType::I32) => write!(f, "{}", "int"),
+            LLVMExpressionType::Integer(integer_type) => write!(f, "{}", integer_type),
+            LLVMExpressionType::Float => write!(f, "{}", "float"),
+            LLVMExpressionType::String => write!(f, "{}", "string"),
             LLVMExpressionType::Boolean => write!(f, "{}", "boolean"),
             LLVMExpressionType::Null => write!(f, "{}", "null"),
             LLVMExpressionType::Array(_, _) => write!(f, "{}", "array"),
@@ -76,12 +190,48 @@ impl fmt::Display for LLVMExpressionType {
 }
 
 impl Expression {
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Expression::Identifier(_, location)
+            | Expression::IntegerLiteral(_, _, location)
+            | Expression::FloatLiteral(_, location)
+            | Expression::StringLiteral(_, location)
+            | Expression::Interpolation(_, _, location)
+            | Expression::Boolean(_, location)
+            | Expression::ArrayElement(_, _, location)
+            | Expression::Slice(_, _, _, location)
+            | Expression::Prefix(_, _, location)
+            | Expression::Infix(_, _, _, location)
+            | Expression::Cast(_, _, location)
+            | Expression::If { location, .. }
+            | Expression::Function { location, .. } => Some(location),
+            Expression::Call(call) => Some(&call.location),
+            Expression::Array(_, _) => None,
+        }
+    }
+
     pub fn string(&self) -> String {
         match self {
             Expression::Identifier(ident, _location) => ident.0.to_string(),
-            Expression::IntegerLiteral(int, _location) => int.to_string(),
+            Expression::IntegerLiteral(int, None, _location) => int.to_string(),
+            Expression::IntegerLiteral(int, Some(integer_type), _location) => {
+                format!("{}{}", int, integer_type)
+            }
+            Expression::FloatLiteral(float, _location) => format!("{:?}", float),
             Expression::StringLiteral(literal, _location) => {
-                format!(r#""{}""#, literal.to_string())
+                format!("{:?}", literal)
+            }
+            Expression::Interpolation(texts, expressions, _location) => {
+                let mut string = String::from("\"");
+                for (index, text) in texts.iter().enumerate() {
+                    let quoted = format!("{:?}", text);
+                    string.push_str(&quoted[1..quoted.len() - 1].replace("${", "\\${"));
+                    if let Some(expression) = expressions.get(index) {
+                        string.push_str(&format!("${{{}}}", expression.string()));
+                    }
+                }
+                string.push('"');
+                string
             }
             Expression::Boolean(boolean, _location) => boolean.to_string(),
             Expression::Array(_, elements) => {
@@ -98,10 +248,25 @@ impl Expression {
             Expression::ArrayElement(ident, index_expression, _) => {
                 format!("{}[{}]", ident.0.to_string(), index_expression.string())
             }
+            Expression::Slice(ident, start, end, _location) => {
+                let bound_string = |bound: &Option<Box<Expression>>| match bound {
+                    Some(expression) => expression.string(),
+                    None => String::new(),
+                };
+                format!(
+                    "{}[{}:{}]",
+                    ident.0.to_string(),
+                    bound_string(start),
+                    bound_string(end)
+                )
+            }
             Expression::Prefix(prefix, expr, _location) => format!("({}{})", prefix, expr.string()),
             Expression::Infix(infix, left, right, _location) => {
                 format!("({} {} {})", left.string(), infix, right.string())
             }
+            Expression::Cast(expr, expression_type, _location) => {
+                format!("({} as {})", expr.string(), expression_type)
+            }
             Expression::If {
                 conditions,
                 bodies,
@@ -141,15 +306,22 @@ impl Expression {
                 parameters,
                 body,
                 parameter_types,
+                parameter_mutabilities,
                 return_type,
                 location: _,
             } => {
                 let mut param_string = String::new();
                 for (index, Identifier(ref string)) in parameters.iter().enumerate() {
                     if index == 0 {
-                        param_string.push_str(&format!("{}: {}", string, parameter_types[index]));
+                        param_string.push_str(&format!(
+                            "{}{}: {}",
+                            parameter_mutabilities[index], string, parameter_types[index]
+                        ));
                     } else {
-                        param_string.push_str(&format!(", {}: {}", string, parameter_types[index]));
+                        param_string.push_str(&format!(
+                            ", {}{}: {}",
+                            parameter_mutabilities[index], string, parameter_types[index]
+                        ));
                     }
                 }
                 let mut ret_string = String::new();
@@ -6,6 +6,7 @@ pub enum Infix {
     Minus,
     Divide,
     Multiply,
+    Power,
     Rem,
     Eq,
     NotEq,
@@ -22,6 +23,7 @@ impl fmt::Display for Infix {
             Infix::Minus => write!(f, "-"),
             Infix::Divide => write!(f, "/"),
             Infix::Multiply => write!(f, "*"),
+            Infix::Power => write!(f, "**"),
             Infix::Rem => write!(f, "%"),
             Infix::Eq => write!(f, "=="),
             Infix::NotEq => write!(f, "!="),
@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use parser::expressions::*;
+
+// the same name shares one allocation no matter how often it appears
+#[derive(Debug, Default)]
+pub struct Interner<'a> {
+    names: HashMap<&'a str, Rc<str>>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new() -> Interner<'a> {
+        Interner {
+            names: HashMap::new(),
+        }
+    }
+
+    pub fn intern(&mut self, name: &'a str) -> Identifier {
+        let interned = self.names.entry(name).or_insert_with(|| Rc::from(name));
+        Identifier(interned.clone())
+    }
+}
+
+#[test]
+fn intern_same_name() {
+    let mut interner = Interner::new();
+    let Identifier(first) = interner.intern("abc");
+    let Identifier(second) = interner.intern("abc");
+    let Identifier(third) = interner.intern("def");
+    assert!(Rc::ptr_eq(&first, &second));
+    assert!(Rc::ptr_eq(&first, &third) == false);
+}
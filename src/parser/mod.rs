@@ -1,6 +1,7 @@
 pub mod converter;
 pub mod expressions;
 pub mod infix;
+pub mod interner;
 pub mod parser;
 pub mod precedence;
 pub mod prefix;
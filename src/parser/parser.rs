@@ -1,18 +1,23 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
 use lexer::lexer::*;
 use lexer::token::*;
 
 use parser::converter::*;
 use parser::expressions::*;
 use parser::infix::*;
+use parser::interner::*;
 use parser::precedence::*;
 use parser::prefix::*;
 use parser::statements::*;
 
 pub struct Parser<'a> {
     pub lexer: &'a mut Lexer<'a>,
-    pub cur_token: Option<Token>,
-    pub peek_token: Option<Token>,
+    pub cur_token: Option<Token<'a>>,
+    pub peek_token: Option<Token<'a>>,
     pub errors: Vec<String>,
+    pub interner: Interner<'a>,
 }
 
 impl<'a> Parser<'a> {
@@ -20,38 +25,52 @@ impl<'a> Parser<'a> {
         let current_token = lexer.next_token();
         let peek_token = lexer.next_token();
 
+        let errors = lexer.errors.drain(..).collect();
+
         Parser {
             lexer: lexer,
             cur_token: current_token,
             peek_token: peek_token,
-            errors: Vec::new(),
+            errors: errors,
+            interner: Interner::new(),
         }
     }
 
     pub fn next_token(&mut self) {
-        self.cur_token = self.peek_token.to_owned();
+        self.cur_token = self.peek_token.take();
         self.peek_token = self.lexer.next_token();
+        self.errors.append(&mut self.lexer.errors);
     }
 
     pub fn parse_program(&mut self) -> Program {
         let mut program = Vec::new();
 
         while self.cur_token != None {
-            if let Some(stmt) = self.parse_statement() {
-                program.push(stmt);
+            match self.parse_statement() {
+                Some(stmt) => program.push(stmt),
+                None => self.skip_failed_statement(),
             }
             self.next_token();
         }
         program
     }
 
+    // the semicolons of a statement which failed to parse are not statements of their own
+    pub fn skip_failed_statement(&mut self) {
+        while self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+    }
+
     pub fn parse_statement(&mut self) -> Option<Statement> {
-        if let Some(token) = self.cur_token.to_owned() {
-            return match token.kind {
+        if let Some(kind) = self.cur_token_kind() {
+            return match kind {
                 TokenType::Identifier => self.handle_identifier(),
                 TokenType::Let => self.parse_let_statement(),
                 TokenType::Return => self.parse_return_statement(),
                 TokenType::While => self.parse_while_statement(),
+                TokenType::Extern => self.parse_extern_statement(),
+                TokenType::Export => self.parse_export_statement(),
                 _ => self.parse_expression_statement(),
             };
         } else {
@@ -92,9 +111,14 @@ impl<'a> Parser<'a> {
         }
 
         match maybe_array.clone() {
-            Expression::ArrayElement(ident, index_expression, _) => Some(
-                Statement::AssignmentAggregate(ident, assign_expression, *index_expression),
-            ),
+            Expression::ArrayElement(ident, index_expression, location) => {
+                Some(Statement::AssignmentAggregate(
+                    ident,
+                    assign_expression,
+                    *index_expression,
+                    location,
+                ))
+            }
             _ => {
                 panic!("{:?} cannot be assigned", maybe_array);
             }
@@ -102,60 +126,74 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_assign_statement(&mut self) -> Option<Statement> {
-        if let Some(token) = self.cur_token.to_owned() {
-            let name = Identifier(token.value.to_owned());
-
-            if self.expect_peek(TokenType::Assign) == false {
-                return None;
-            }
+        let location = match &self.cur_token {
+            Some(token) => Location::new(token.current_row, token.current_column),
+            None => return None,
+        };
+        let name = self.intern_cur_token()?;
 
-            self.next_token();
-            let value = if let Some(value) = self.parse_expression(Precedences::Lowest) {
-                value
-            } else {
-                return None;
-            };
+        if self.expect_peek(TokenType::Assign) == false {
+            return None;
+        }
 
-            while self.peek_token_is(TokenType::Semicolon) {
-                self.next_token();
-            }
+        self.next_token();
+        let value = if let Some(value) = self.parse_expression(Precedences::Lowest) {
+            value
+        } else {
+            return None;
+        };
 
-            return Some(Statement::Assignment(name, value));
+        while self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
         }
-        None
+
+        Some(Statement::Assignment(name, value, location))
     }
 
     pub fn parse_let_statement(&mut self) -> Option<Statement> {
+        let mutability = if self.peek_token_is(TokenType::Mut) {
+            self.next_token();
+            Mutability::Mutable
+        } else {
+            Mutability::Immutable
+        };
+
         if self.expect_peek(TokenType::Identifier) == false {
             return None;
         }
 
-        if let Some(token) = self.cur_token.to_owned() {
-            let name = Identifier(token.value.to_owned());
+        let name = self.intern_cur_token()?;
 
-            if self.expect_peek(TokenType::Assign) == false {
-                return None;
-            }
-
-            self.next_token();
-            let expression = if let Some(value) = self.parse_expression(Precedences::Lowest) {
-                value
-            } else {
-                return None;
-            };
+        if self.expect_peek(TokenType::Assign) == false {
+            return None;
+        }
 
-            let llvm_expression_type = get_expression_llvm_type(&expression);
+        self.next_token();
+        let expression = if let Some(value) = self.parse_expression(Precedences::Lowest) {
+            value
+        } else {
+            return None;
+        };
 
-            while self.peek_token_is(TokenType::Semicolon) {
-                self.next_token();
-            }
+        let llvm_expression_type = get_expression_llvm_type(&expression);
 
-            return Some(Statement::Let(name, llvm_expression_type, expression));
+        while self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
         }
-        None
+
+        Some(Statement::Let(
+            name,
+            mutability,
+            llvm_expression_type,
+            expression,
+        ))
     }
 
     pub fn parse_return_statement(&mut self) -> Option<Statement> {
+        let location = match &self.cur_token {
+            Some(token) => Location::new(token.current_row, token.current_column),
+            None => self.current_location(),
+        };
         self.next_token();
         let return_value = if let Some(value) = self.parse_expression(Precedences::Lowest) {
             value
@@ -167,7 +205,7 @@ impl<'a> Parser<'a> {
             self.next_token();
         }
 
-        return Some(Statement::Return(return_value));
+        return Some(Statement::Return(return_value, location));
     }
 
     pub fn parse_while_statement(&mut self) -> Option<Statement> {
@@ -192,13 +230,97 @@ impl<'a> Parser<'a> {
         None
     }
 
+    // extern fn name(parameter: type, ...): type;
+    pub fn parse_extern_statement(&mut self) -> Option<Statement> {
+        let location = self.current_location();
+        if self.expect_peek(TokenType::Fn) == false {
+            return None;
+        }
+        if self.expect_peek(TokenType::Identifier) == false {
+            return None;
+        }
+        let name = self.intern_cur_token()?;
+        if self.expect_peek(TokenType::Lparen) == false {
+            return None;
+        }
+
+        let mut parameters = Vec::new();
+        let mut parameter_types = Vec::new();
+        let mut variadic = false;
+        while self.peek_token_is(TokenType::Rparen) == false {
+            if parameters.is_empty() == false && self.expect_peek(TokenType::Comma) == false {
+                return None;
+            }
+            // `...` has to be the last parameter
+            if self.peek_token_is(TokenType::Ellipsis) {
+                self.next_token();
+                variadic = true;
+                break;
+            }
+            if self.expect_peek(TokenType::Identifier) == false {
+                return None;
+            }
+            parameters.push(self.intern_cur_token()?);
+            if self.expect_peek(TokenType::Colon) == false {
+                return None;
+            }
+            parameter_types.push(self.peek_expression_type()?);
+            self.next_token();
+        }
+        if self.expect_peek(TokenType::Rparen) == false {
+            return None;
+        }
+
+        if self.expect_peek(TokenType::Colon) == false {
+            return None;
+        }
+        let return_type = self.peek_expression_type()?;
+        self.next_token();
+
+        while self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Statement::Extern(ExternFunction {
+            name: name,
+            parameters: parameters,
+            parameter_types: parameter_types,
+            variadic: variadic,
+            return_type: return_type,
+            location: location,
+        }))
+    }
+
+    // `export fn add(a: int, b: int): int { ... }` defines a function callable from C
+    pub fn parse_export_statement(&mut self) -> Option<Statement> {
+        let location = self.current_location();
+        if self.expect_peek(TokenType::Fn) == false {
+            return None;
+        }
+        if self.expect_peek(TokenType::Identifier) == false {
+            return None;
+        }
+        let name = self.intern_cur_token()?;
+        let function = match self.parse_function_literal() {
+            Some(function) => function,
+            None => return None,
+        };
+
+        while self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Statement::Export(name, function, location))
+    }
+
     pub fn parse_block_statement(&mut self) -> Option<BlockStatement> {
         let mut block = Vec::new();
         self.next_token();
 
         while self.cur_token_is(TokenType::Rbrace) == false && self.cur_token.is_none() == false {
-            if let Some(stmt) = self.parse_statement() {
-                block.push(stmt);
+            match self.parse_statement() {
+                Some(stmt) => block.push(stmt),
+                None => self.skip_failed_statement(),
             }
             self.next_token();
         }
@@ -221,19 +343,25 @@ impl<'a> Parser<'a> {
 
     pub fn parse_expression(&mut self, precedence: Precedences) -> Option<Expression> {
         let mut left_exp: Option<Expression> = None;
-        if let Some(token) = self.cur_token.to_owned() {
-            left_exp = match token.kind {
+        if let Some(kind) = self.cur_token_kind() {
+            left_exp = match kind {
                 TokenType::Bang | TokenType::Minus => self.parse_prefix_expression(),
-                TokenType::Digit => self.parse_integer_literal(),
+                TokenType::Digit => self.parse_integer_literal(false),
+                // already reported by the lexer
+                TokenType::Illegal => return None,
+                TokenType::Float => self.parse_float_literal(),
                 TokenType::Fn => self.parse_function_literal(),
                 TokenType::Identifier => self.parse_identifier(),
                 TokenType::If => self.parse_if_expression(),
                 TokenType::Lbracket => self.parse_array(),
                 TokenType::Lparen => self.parse_grouped_expression(),
                 TokenType::String => self.parse_string_literal(),
+                TokenType::StringStart => self.parse_interpolation(),
                 TokenType::True | TokenType::False => self.parse_boolean(),
                 _ => {
-                    self.no_prefix_parse_fn_error(token);
+                    if let Some(token) = &self.cur_token {
+                        self.errors.push(no_prefix_parse_fn_error(token));
+                    }
                     return None;
                 }
             };
@@ -242,8 +370,8 @@ impl<'a> Parser<'a> {
         while self.peek_token_is(TokenType::Semicolon) == false
             && precedence < self.peek_precedence()
         {
-            if let Some(token) = self.peek_token.to_owned() {
-                left_exp = match token.kind {
+            if let Some(kind) = self.peek_token_kind() {
+                left_exp = match kind {
                     TokenType::Divide
                     | TokenType::Eq
                     | TokenType::Gt
@@ -254,6 +382,7 @@ impl<'a> Parser<'a> {
                     | TokenType::Multiply
                     | TokenType::NotEq
                     | TokenType::Plus
+                    | TokenType::Power
                     | TokenType::Rem => {
                         self.next_token();
                         self.parse_infix_expression(left_exp)
@@ -262,8 +391,14 @@ impl<'a> Parser<'a> {
                         self.next_token();
                         self.parse_call_expression(left_exp)
                     }
+                    TokenType::As => {
+                        self.next_token();
+                        self.parse_cast_expression(left_exp)
+                    }
                     _ => {
-                        self.no_prefix_parse_fn_error(token);
+                        if let Some(token) = &self.peek_token {
+                            self.errors.push(no_prefix_parse_fn_error(token));
+                        }
                         return left_exp;
                     }
                 };
@@ -273,32 +408,93 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_prefix_expression(&mut self) -> Option<Expression> {
-        if let Some(token) = self.cur_token.to_owned() {
+        let (kind, token_location) = match &self.cur_token {
+            Some(token) => (
+                token.kind,
+                Location::new(token.current_row, token.current_column),
+            ),
+            None => return None,
+        };
+        self.next_token();
+        // a negative literal may use the full range of a signed type, e.g. -128i8
+        let mut right = if kind == TokenType::Minus
+            && self.cur_token_is(TokenType::Digit)
+            && !self.peek_token_is(TokenType::Power)
+        {
+            self.parse_integer_literal(true)
+        } else {
+            self.parse_expression(Precedences::Prefix)
+        };
+        // ** binds tighter than a prefix, so -a ** b is -(a ** b)
+        if self.peek_token_is(TokenType::Power) {
             self.next_token();
-            if let Some(right) = self.parse_expression(Precedences::Prefix) {
-                if let Some(prefix) = self.convert_token_to_prefix(token) {
-                    return Some(Expression::Prefix(
-                        prefix,
-                        Box::new(right),
-                        Location::new(self.lexer.current_row),
-                    ));
-                }
+            right = self.parse_infix_expression(right);
+        }
+        if let Some(right) = right {
+            if let Some(prefix) = self.convert_token_to_prefix(kind, &token_location) {
+                return Some(Expression::Prefix(
+                    prefix,
+                    Box::new(right),
+                    self.current_location(),
+                ));
             }
         }
         None
     }
 
-    pub fn parse_integer_literal(&mut self) -> Option<Expression> {
+    pub fn parse_integer_literal(&mut self, negative: bool) -> Option<Expression> {
         if let Some(token) = &self.cur_token {
-            if let Ok(value) = token.value.parse::<u64>() {
+            let suffix_index = token
+                .value
+                .find(|c| c == 'i' || c == 'u')
+                .unwrap_or(token.value.len());
+            let (digits, suffix) = token.value.split_at(suffix_index);
+            let integer_type = IntegerType::from_suffix(suffix);
+
+            let digits = digits.replace("_", "");
+            let (radix, digits) = match digits.get(0..2) {
+                Some("0x") => (16, &digits[2..]),
+                Some("0b") => (2, &digits[2..]),
+                Some("0o") => (8, &digits[2..]),
+                _ => (10, &digits[..]),
+            };
+
+            if let Ok(value) = u64::from_str_radix(digits, radix) {
+                if let Some(integer_type) = integer_type {
+                    if integer_type.fits(value, negative) == false {
+                        self.errors.push(format!(
+                            "integer literal {}{} does not fit in {}. row: {}, column: {}",
+                            if negative { "-" } else { "" },
+                            token.value,
+                            integer_type,
+                            token.current_row,
+                            token.current_column
+                        ));
+                    }
+                }
                 return Some(Expression::IntegerLiteral(
                     value,
-                    Location::new(self.lexer.current_row),
+                    integer_type,
+                    self.current_location(),
                 ));
             } else {
                 self.errors.push(format!(
-                    "could not parse {} as integer. row: {}",
-                    token.value, token.current_row
+                    "integer literal {} is too large. row: {}, column: {}",
+                    token.value, token.current_row, token.current_column
+                ));
+            }
+        }
+        None
+    }
+
+    pub fn parse_float_literal(&mut self) -> Option<Expression> {
+        if let Some(token) = &self.cur_token {
+            if let Ok(value) = token.value.replace("_", "").parse::<f64>() {
+                return Some(Expression::FloatLiteral(value, self.current_location()));
+            } else {
+                self.errors.push(format!(
+                    "could not parse {} as float. row: {}, column: {}",
+                    token.value, token.current_row, token.current_column
                 ));
             }
         }
@@ -310,7 +506,8 @@ impl<'a> Parser<'a> {
             return None;
         }
 
-        let (parameters, parameter_types, return_type) = self.parse_function_parameters();
+        let (parameters, parameter_types, parameter_mutabilities, return_type) =
+            self.parse_function_parameters();
 
         if self.expect_peek(TokenType::Lbrace) == false {
             return None;
@@ -320,9 +517,10 @@ impl<'a> Parser<'a> {
             return Some(Expression::Function {
                 parameters: parameters,
                 parameter_types: parameter_types,
+                parameter_mutabilities: parameter_mutabilities,
                 body: body,
                 return_type: return_type,
-                location: Location::new(self.lexer.current_row),
+                location: self.current_location(),
             });
         }
         None
@@ -330,25 +528,32 @@ impl<'a> Parser<'a> {
 
     pub fn parse_function_parameters(
         &mut self,
-    ) -> (Vec<Identifier>, Vec<LLVMExpressionType>, LLVMExpressionType) {
+    ) -> (
+        Vec<Identifier>,
+        Vec<LLVMExpressionType>,
+        Vec<Mutability>,
+        LLVMExpressionType,
+    ) {
         let mut parameters = Vec::new();
         let mut parameter_types = Vec::new();
+        let mut parameter_mutabilities = Vec::new();
 
         if self.peek_token_is(TokenType::Rparen) {
             self.next_token();
-            return self.parser_return_type(parameters, parameter_types);
+            return self.parser_return_type(parameters, parameter_types, parameter_mutabilities);
         }
         self.next_token();
 
-        if let Some(token) = self.cur_token.to_owned() {
-            parameters.push(Identifier(token.value.to_owned()));
+        parameter_mutabilities.push(self.parse_parameter_mutability());
+        if let Some(parameter) = self.intern_cur_token() {
+            parameters.push(parameter);
 
             if self.expect_peek(TokenType::Colon) == false {
                 self.emit_error_for_funciton();
             }
 
-            if let Some(token) = self.peek_token.to_owned() {
-                parameter_types.push(convert_token_to_expression_type(token));
+            if let Some(parameter_type) = self.peek_expression_type() {
+                parameter_types.push(parameter_type);
                 self.next_token();
             }
         }
@@ -357,16 +562,17 @@ impl<'a> Parser<'a> {
             self.next_token();
             self.next_token();
 
-            if let Some(token) = self.cur_token.to_owned() {
-                parameters.push(Identifier(token.value.to_owned()));
+            parameter_mutabilities.push(self.parse_parameter_mutability());
+            if let Some(parameter) = self.intern_cur_token() {
+                parameters.push(parameter);
             }
 
             if self.expect_peek(TokenType::Colon) == false {
                 self.emit_error_for_funciton();
             }
 
-            if let Some(token) = self.peek_token.to_owned() {
-                parameter_types.push(convert_token_to_expression_type(token));
+            if let Some(parameter_type) = self.peek_expression_type() {
+                parameter_types.push(parameter_type);
                 self.next_token();
             }
         }
@@ -375,24 +581,39 @@ impl<'a> Parser<'a> {
             self.emit_error_for_funciton();
         }
 
-        self.parser_return_type(parameters, parameter_types)
+        self.parser_return_type(parameters, parameter_types, parameter_mutabilities)
+    }
+
+    pub fn parse_parameter_mutability(&mut self) -> Mutability {
+        if self.cur_token_is(TokenType::Mut) {
+            self.next_token();
+            return Mutability::Mutable;
+        }
+        Mutability::Immutable
     }
 
     pub fn parser_return_type(
         &mut self,
         parameters: Vec<Identifier>,
         parameter_types: Vec<LLVMExpressionType>,
-    ) -> (Vec<Identifier>, Vec<LLVMExpressionType>, LLVMExpressionType) {
+        parameter_mutabilities: Vec<Mutability>,
+    ) -> (
+        Vec<Identifier>,
+        Vec<LLVMExpressionType>,
+        Vec<Mutability>,
+        LLVMExpressionType,
+    ) {
         if self.expect_peek(TokenType::Colon) == false {
             self.emit_error_for_funciton();
         }
 
-        if let Some(token) = self.peek_token.to_owned() {
+        if let Some(return_type) = self.peek_expression_type() {
             self.next_token();
             return (
                 parameters,
                 parameter_types,
-                convert_token_to_expression_type(token),
+                parameter_mutabilities,
+                return_type,
             );
         }
 
@@ -401,39 +622,62 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_identifier(&mut self) -> Option<Expression> {
-        if let Some(token) = self.cur_token.clone() {
-            if self.peek_token_is(TokenType::Lbracket) {
-                return self.parse_array_element(token);
-            }
-
-            return Some(Expression::Identifier(
-                Identifier(token.value.to_owned()),
-                Location::new(self.lexer.current_row),
-            ));
+        let token_location = match &self.cur_token {
+            Some(token) => Location::new(token.current_row, token.current_column),
+            None => return None,
+        };
+        let ident = self.intern_cur_token()?;
+        if self.peek_token_is(TokenType::Lbracket) {
+            return self.parse_array_element(ident, token_location);
         }
-        None
+
+        Some(Expression::Identifier(ident, self.current_location()))
     }
 
-    pub fn parse_array_element(&mut self, token: Token) -> Option<Expression> {
+    // a[index], or a slice like s[start:end] where both bounds are optional
+    pub fn parse_array_element(
+        &mut self,
+        ident: Identifier,
+        location: Location,
+    ) -> Option<Expression> {
         self.next_token();
         self.next_token();
-        if let Some(index_expression) = self.parse_expression(Precedences::Lowest) {
-            while self.cur_token_is(TokenType::Rbracket) == false {
-                self.next_token();
+
+        let start = if self.cur_token_is(TokenType::Colon) {
+            None
+        } else {
+            let index_expression = self.parse_expression(Precedences::Lowest)?;
+            if self.peek_token_is(TokenType::Colon) == false {
+                while self.cur_token_is(TokenType::Rbracket) == false && self.cur_token.is_some() {
+                    self.next_token();
+                }
+
+                return Some(Expression::ArrayElement(
+                    ident,
+                    Box::new(index_expression),
+                    location,
+                ));
             }
+            self.next_token();
+            Some(Box::new(index_expression))
+        };
 
-            return Some(Expression::ArrayElement(
-                Identifier(token.value.to_owned()),
-                Box::new(index_expression),
-                Location::new(self.lexer.current_row),
-            ));
+        let end = if self.peek_token_is(TokenType::Rbracket) {
+            None
         } else {
+            self.next_token();
+            Some(Box::new(self.parse_expression(Precedences::Lowest)?))
+        };
+
+        if self.expect_peek(TokenType::Rbracket) == false {
             return None;
         }
+        Some(Expression::Slice(ident, start, end, location))
     }
 
     pub fn parse_if_expression(&mut self) -> Option<Expression> {
         let if_row = self.lexer.current_row;
+        let if_column = self.current_location().column;
         let mut condtions = Vec::new();
         let mut bodies = Vec::new();
         let mut loop_flag = true;
@@ -471,7 +715,7 @@ impl<'a> Parser<'a> {
 
         if self.peek_token_is(TokenType::Else) {
             self.next_token();
-            condtions.push(Expression::Boolean(true, Location::new(if_row)));
+            condtions.push(Expression::Boolean(true, Location::new(if_row, if_column)));
             if self.expect_peek(TokenType::Lbrace) == false {
                 return None;
             }
@@ -482,20 +726,20 @@ impl<'a> Parser<'a> {
                 bodies.push(Vec::new());
             }
         } else {
-            condtions.push(Expression::Boolean(false, Location::new(if_row)));
+            condtions.push(Expression::Boolean(false, Location::new(if_row, if_column)));
             bodies.push(Vec::new());
         };
 
         return Some(Expression::If {
             conditions: condtions,
             bodies: bodies,
-            location: Location::new(if_row),
+            location: Location::new(if_row, if_column),
         });
     }
 
     pub fn parse_array(&mut self) -> Option<Expression> {
         let mut elements: Vec<Expression> = Vec::new();
-        let mut llvm_expression_type = LLVMExpressionType::Integer;
+        let mut llvm_expression_type = LLVMExpressionType::Integer(IntegerType::I32);
 
         if self.peek_token_is(TokenType::Rbracket) == true {
             return Some(Expression::Array(llvm_expression_type, elements)); // TODO
@@ -538,11 +782,59 @@ impl<'a> Parser<'a> {
         Some(exp)
     }
 
+    // "a${x}b${y}c" arrives as StringStart(a), x, StringMiddle(b), y, StringEnd(c)
+    pub fn parse_interpolation(&mut self) -> Option<Expression> {
+        let location = self.current_location();
+        let mut texts = vec![self.cur_token.as_ref()?.value.to_string()];
+        let mut expressions = Vec::new();
+        loop {
+            self.next_token();
+            expressions.push(self.parse_expression(Precedences::Lowest)?);
+            self.next_token();
+
+            let token = self.cur_token.as_ref()?;
+            match token.kind {
+                TokenType::StringMiddle => texts.push(token.value.to_string()),
+                TokenType::StringEnd => {
+                    texts.push(token.value.to_string());
+                    break;
+                }
+                _ => {
+                    self.errors.push(format!(
+                        "expected }} to close interpolation, but got {:?}. row: {}, column: {}",
+                        token.kind, token.current_row, token.current_column
+                    ));
+                    self.skip_interpolation();
+                    return None;
+                }
+            }
+        }
+        Some(Expression::Interpolation(texts, expressions, location))
+    }
+
+    // skips the rest of a broken interpolated string, including nested ones
+    pub fn skip_interpolation(&mut self) {
+        let mut depth = 1;
+        while let Some(kind) = self.cur_token_kind() {
+            match kind {
+                TokenType::StringStart => depth += 1,
+                TokenType::StringEnd => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            self.next_token();
+        }
+    }
+
     pub fn parse_string_literal(&mut self) -> Option<Expression> {
         if let Some(token) = &self.cur_token {
             return Some(Expression::StringLiteral(
-                token.value.to_owned(),
-                Location::new(self.lexer.current_row),
+                token.value.to_string(),
+                self.current_location(),
             ));
         }
         None
@@ -551,7 +843,7 @@ impl<'a> Parser<'a> {
     pub fn parse_boolean(&mut self) -> Option<Expression> {
         return Some(Expression::Boolean(
             self.cur_token_is(TokenType::True),
-            Location::new(self.lexer.current_row),
+            self.current_location(),
         ));
     }
 
@@ -560,46 +852,87 @@ impl<'a> Parser<'a> {
             return None;
         }
 
-        if let Some(token) = self.cur_token.to_owned() {
-            let precedence = self.cur_precedence();
-            self.next_token();
-            if let Some(right) = self.parse_expression(precedence) {
-                if let Some(infix) = self.convert_token_to_infix(token.clone()) {
-                    return Some(Expression::Infix(
-                        infix,
-                        Box::new(left.unwrap()),
-                        Box::new(right),
-                        Location::new(self.lexer.current_row),
-                    ));
-                } else {
-                    self.errors.push(format!(
-                        "{:?} {:?} {:?} cannot be parsed. row: {}",
-                        left, token.kind, right, token.current_row
-                    ));
-                }
+        let (kind, location) = match &self.cur_token {
+            Some(token) => (
+                token.kind,
+                Location::new(token.current_row, token.current_column),
+            ),
+            None => return None,
+        };
+        // ** is right associative, so the right side takes another **
+        let precedence = match kind {
+            TokenType::Power => Precedences::Product,
+            _ => self.cur_precedence(),
+        };
+        self.next_token();
+        if let Some(right) = self.parse_expression(precedence) {
+            if let Some(infix) = self.convert_token_to_infix(kind, &location) {
+                return Some(Expression::Infix(
+                    infix,
+                    Box::new(left.unwrap()),
+                    Box::new(right),
+                    location,
+                ));
+            } else {
+                self.errors.push(format!(
+                    "{:?} {:?} {:?} cannot be parsed. row: {}, column: {}",
+                    left, kind, right, location.row, location.column
+                ));
             }
         }
         None
     }
 
+    pub fn parse_cast_expression(&mut self, left: Option<Expression>) -> Option<Expression> {
+        let left = if let Some(left) = left {
+            left
+        } else {
+            return None;
+        };
+
+        if let Some(token) = &self.peek_token {
+            if let TokenType::LLVMTokenType(_) = token.kind {
+                let expression_type = convert_token_to_expression_type(token);
+                self.next_token();
+                return Some(Expression::Cast(
+                    Box::new(left),
+                    expression_type,
+                    self.current_location(),
+                ));
+            }
+            self.errors.push(format!(
+                "expected type after as, but actually {:?}. row: {}, column: {}",
+                token.kind, token.current_row, token.current_column
+            ));
+        }
+        None
+    }
+
     pub fn parse_call_expression(&mut self, function: Option<Expression>) -> Option<Expression> {
         if let Some(function) = function {
+            let arguments = self.parse_call_arguments();
+            // a call of a name is located at the name
+            let location = match function {
+                Expression::Identifier(_, ref location) => location.clone(),
+                _ => self.current_location(),
+            };
             let expr = Expression::Call(Call {
                 function: Box::new(function),
-                arguments: self.parse_call_arguments(),
-                location: Location::new(self.lexer.current_row),
+                arguments: arguments,
+                location: location,
             });
 
             match expr.clone() {
                 Expression::Function {
                     parameters: _,
                     parameter_types: _,
+                    parameter_mutabilities: _,
                     body: _,
                     return_type: _,
                     location: _,
                 } => {
-                    if let Some(token) = self.peek_token.to_owned() {
-                        return match token.kind {
+                    if let Some(kind) = self.peek_token_kind() {
+                        return match kind {
                             TokenType::Lparen => {
                                 self.next_token();
                                 self.parse_call_expression(Some(expr))
@@ -642,12 +975,17 @@ impl<'a> Parser<'a> {
         args
     }
 
-    pub fn convert_token_to_infix(&mut self, token: Token) -> Option<Infix> {
-        match token.kind {
+    pub fn convert_token_to_infix(
+        &mut self,
+        kind: TokenType,
+        location: &Location,
+    ) -> Option<Infix> {
+        match kind {
             TokenType::Plus => Some(Infix::Plus),
             TokenType::Minus => Some(Infix::Minus),
             TokenType::Divide => Some(Infix::Divide),
             TokenType::Multiply => Some(Infix::Multiply),
+            TokenType::Power => Some(Infix::Power),
             TokenType::Rem => Some(Infix::Rem),
             TokenType::Eq => Some(Infix::Eq),
             TokenType::NotEq => Some(Infix::NotEq),
@@ -657,23 +995,27 @@ impl<'a> Parser<'a> {
             TokenType::Lt => Some(Infix::Lt),
             _ => {
                 self.errors.push(format!(
-                    "{:?} is not a token for infix. row: {}",
-                    token.kind, token.current_row
+                    "{:?} is not a token for infix. row: {}, column: {}",
+                    kind, location.row, location.column
                 ));
                 None
             }
         }
     }
 
-    pub fn convert_token_to_prefix(&mut self, token: Token) -> Option<Prefix> {
-        match token.kind {
+    pub fn convert_token_to_prefix(
+        &mut self,
+        kind: TokenType,
+        location: &Location,
+    ) -> Option<Prefix> {
+        match kind {
             TokenType::Plus => Some(Prefix::Plus),
             TokenType::Minus => Some(Prefix::Minus),
             TokenType::Bang => Some(Prefix::Bang),
             _ => {
                 self.errors.push(format!(
-                    "{:?} is not a token for prefix. row: {}",
-                    token.kind, token.current_row
+                    "{:?} is not a token for prefix. row: {}, column: {}",
+                    kind, location.row, location.column
                 ));
                 None
             }
@@ -690,6 +1032,38 @@ impl<'a> Parser<'a> {
         Precedences::Lowest
     }
 
+    // the token and the interner are separate fields, so nothing is cloned
+    pub fn intern_cur_token(&mut self) -> Option<Identifier> {
+        let token = self.cur_token.as_ref()?;
+        Some(match token.value {
+            Cow::Borrowed(name) => self.interner.intern(name),
+            Cow::Owned(ref name) => Identifier(Rc::from(name.as_str())),
+        })
+    }
+
+    pub fn peek_expression_type(&self) -> Option<LLVMExpressionType> {
+        self.peek_token
+            .as_ref()
+            .map(convert_token_to_expression_type)
+    }
+
+    // token kinds are Copy, so peeking at them does not clone the token
+    pub fn cur_token_kind(&self) -> Option<TokenType> {
+        self.cur_token.as_ref().map(|token| token.kind)
+    }
+
+    pub fn peek_token_kind(&self) -> Option<TokenType> {
+        self.peek_token.as_ref().map(|token| token.kind)
+    }
+
+    // the row of the lexer and the column of the current token
+    pub fn current_location(&self) -> Location {
+        match &self.cur_token {
+            Some(token) => Location::new(token.current_row, token.current_column),
+            None => Location::new(self.lexer.current_row, 0),
+        }
+    }
+
     pub fn cur_token_is(&self, token_type: TokenType) -> bool {
         if let Some(token) = &self.cur_token {
             return token.kind == token_type;
@@ -710,9 +1084,7 @@ impl<'a> Parser<'a> {
             self.next_token();
             return true;
         } else {
-            if let Some(token) = self.peek_token.clone() {
-                self.peek_error(token);
-            }
+            self.peek_error();
             return false;
         }
     }
@@ -721,18 +1093,13 @@ impl<'a> Parser<'a> {
         self.errors.len() > 0
     }
 
-    pub fn no_prefix_parse_fn_error(&mut self, token: Token) {
-        self.errors.push(format!(
-            "no prefix parse function for {:?}. row: {}",
-            token.kind, token.current_row
-        ));
-    }
-
-    pub fn peek_error(&mut self, token: Token) {
-        self.errors.push(format!(
-            "expected next token to be {:?} instead. row: {}",
-            token.kind, token.current_row
-        ));
+    pub fn peek_error(&mut self) {
+        if let Some(token) = &self.peek_token {
+            self.errors.push(format!(
+                "expected next token to be {:?} instead. row: {}, column: {}",
+                token.kind, token.current_row, token.current_column
+            ));
+        }
     }
 
     pub fn peek_token_is(&mut self, token_type: TokenType) -> bool {
@@ -753,6 +1120,13 @@ impl<'a> Parser<'a> {
     }
 }
 
+pub fn no_prefix_parse_fn_error(token: &Token) -> String {
+    format!(
+        "no prefix parse function for {:?}. row: {}, column: {}",
+        token.kind, token.current_row, token.current_column
+    )
+}
+
 /* below the test implementation */
 #[allow(dead_code)]
 fn statement_assert(statement: &Statement, expect: &str) {
@@ -764,6 +1138,72 @@ fn statement_assert(statement: &Statement, expect: &str) {
     );
 }
 
+// four lines and two statements for every block
+#[allow(dead_code)]
+fn large_input(blocks: usize) -> String {
+    let mut input = String::new();
+    for index in 0..blocks {
+        input.push_str(&format!(
+            "let mut value_{} = {} + 0x10 * 2;\n",
+            index, index
+        ));
+        input.push_str(&format!("if (value_{} > 10) {{\n", index));
+        input.push_str("  printf(\"large \\\"input\\\"\\n\");\n");
+        input.push_str("}\n");
+    }
+    input
+}
+
+#[test]
+fn parse_large_input() {
+    let input = large_input(2_500);
+
+    // names and numbers are slices of the input, so lexing them does not allocate
+    let mut lexer = Lexer::new(&input);
+    while let Some(token) = lexer.next_token() {
+        match (token.kind, &token.value) {
+            (TokenType::Identifier, Cow::Owned(_)) | (TokenType::Digit, Cow::Owned(_)) => {
+                panic!("{:?} is not borrowed from the input", token)
+            }
+            _ => {}
+        }
+    }
+
+    // every use of a name shares the allocation of its declaration
+    let program = parse_input(&input);
+    assert_eq!(program.len(), 5_000);
+    for statements in program.chunks(2) {
+        let declared = match &statements[0] {
+            Statement::Let(Identifier(name), _, _, _) => name,
+            statement => panic!("expected let, but got {:?}", statement),
+        };
+        let used = match &statements[1] {
+            Statement::Expression(Expression::If { conditions, .. }) => match &conditions[0] {
+                Expression::Infix(_, left, _, _) => match **left {
+                    Expression::Identifier(Identifier(ref name), _) => name.clone(),
+                    ref left => panic!("expected identifier, but got {:?}", left),
+                },
+                condition => panic!("expected infix, but got {:?}", condition),
+            },
+            statement => panic!("expected if, but got {:?}", statement),
+        };
+        assert!(Rc::ptr_eq(declared, &used), "{} is not interned", declared);
+    }
+}
+
+// run with `cargo test --release parse_large_input_benchmark -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn parse_large_input_benchmark() {
+    use std::time::Instant;
+
+    let input = large_input(25_000);
+    let start = Instant::now();
+    let program = parse_input(&input);
+    println!("parsed 100000 lines in {:?}", start.elapsed());
+    assert_eq!(program.len(), 50_000);
+}
+
 #[allow(dead_code)]
 fn parse_input(input: &str) -> Program {
     let mut lexer = Lexer::new(input);
@@ -804,6 +1244,114 @@ fn let_statements() {
     statement_assert(&program[3], "let foobar = 939393");
 }
 
+#[test]
+fn let_mut_statements() {
+    let input = r#"
+    let mut x = 5;
+    let y = x;
+  "#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "let mut x = 5");
+    statement_assert(&program[1], "let y = x");
+}
+
+#[test]
+fn float_statements() {
+    let input = r#"
+    let x = 3.14;
+    let y = 1e-3 * 2.0;
+  "#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "let x = 3.14");
+    statement_assert(&program[1], "let y = (0.001 * 2.0)");
+}
+
+#[test]
+fn sized_integer_statements() {
+    let input = r#"
+    let x = 10u8;
+    let y = x as i64 * 2;
+    let z = -x as u32;
+  "#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "let x = 10u8");
+    statement_assert(&program[1], "let y = ((x as i64) * 2)");
+    statement_assert(&program[2], "let z = ((-x) as u32)");
+}
+
+#[test]
+fn integer_literal_out_of_range() {
+    let input = r#"
+    let x = 256u8;
+  "#;
+    parse_and_emit_error(
+        input,
+        vec!["integer literal 256u8 does not fit in u8. row: 1, column: 12"],
+    );
+}
+
+#[test]
+fn radix_integer_statements() {
+    let input = r#"
+    let a = 0xFF;
+    let b = 0b1010u8;
+    let c = 0o17 + 1_000;
+    let d = -128i8;
+  "#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "let a = 255");
+    statement_assert(&program[1], "let b = 10u8");
+    statement_assert(&program[2], "let c = (15 + 1000)");
+    statement_assert(&program[3], "let d = (-128i8)");
+}
+
+#[test]
+fn numeric_literal_errors() {
+    let input = r#"
+    let a = 0x100u8;
+    let b = -129i8;
+    let c = 18446744073709551616;
+    let d = 12abc;
+  "#;
+    parse_and_emit_error(
+        input,
+        vec![
+            "integer literal 0x100u8 does not fit in u8. row: 1, column: 12",
+            "integer literal -129i8 does not fit in i8. row: 2, column: 13",
+            "integer literal 18446744073709551616 is too large. row: 3, column: 12",
+            "invalid numeric literal 12abc. row: 4, column: 12",
+        ],
+    );
+}
+
+#[test]
+fn stray_semicolon() {
+    let input = r#"
+    ;
+    let a = 1;
+  "#;
+    parse_and_emit_error(
+        input,
+        vec!["no prefix parse function for Semicolon. row: 1, column: 4"],
+    );
+}
+
+#[test]
+fn string_escape_statements() {
+    let input = r#"
+    let s = "a\tb\"c\u{3042}";
+  "#;
+    let program = parse_input(input);
+    statement_assert(&program[0], r#"let s = "a\tb\"cあ""#);
+    match &program[0] {
+        // 1 + 1 + 1 + 1 + 1 + 3 bytes
+        Statement::Let(_, _, _, Expression::StringLiteral(string, _)) => {
+            assert_eq!(string.len(), 8)
+        }
+        _ => panic!("{:?} is not a let statement", program[0]),
+    }
+}
+
 #[test]
 fn return_statements() {
     let input = r#"
@@ -867,6 +1415,10 @@ fn operator_precedence_parsing() {
   5 > 4 == 3 < 4;
   5 < 4 != 3 > 4;
   3 + 4 * 5 == 3 * 1 + 4 * 5;
+  a * b ** c;
+  a ** b ** c;
+  -a ** b;
+  -2 ** 2 * c;
 "#;
     let program = parse_input(input);
     statement_assert(&program[0], "((-a) * b)");
@@ -881,6 +1433,10 @@ fn operator_precedence_parsing() {
     statement_assert(&program[9], "((5 > 4) == (3 < 4))");
     statement_assert(&program[10], "((5 < 4) != (3 > 4))");
     statement_assert(&program[11], "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))");
+    statement_assert(&program[12], "(a * (b ** c))");
+    statement_assert(&program[13], "(a ** (b ** c))");
+    statement_assert(&program[14], "(-(a ** b))");
+    statement_assert(&program[15], "((-(2 ** 2)) * c)");
 }
 
 #[test]
@@ -934,12 +1490,43 @@ fn array_element_parsing() {
     statement_assert(&program[1], "a[1]");
 }
 
+#[test]
+fn interpolation_parsing() {
+    let input = r#"
+    "${n} items for ${name}\n";
+    "${"a" + "${b}"}\${c}";
+"#;
+    let program = parse_input(input);
+    statement_assert(&program[0], r#""${n} items for ${name}\n""#);
+    statement_assert(&program[1], r#""${("a" + "${b}")}\${c}""#);
+
+    parse_and_emit_error(
+        r#""${x y}";"#,
+        vec!["expected } to close interpolation, but got Identifier. row: 0, column: 5"],
+    );
+}
+
+#[test]
+fn slice_parsing() {
+    let input = r#"
+    s[1:i + 1];
+    s[:2];
+    s[3:];
+"#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "s[1:(i + 1)]");
+    statement_assert(&program[1], "s[:2]");
+    statement_assert(&program[2], "s[3:]");
+}
+
 #[test]
 fn funciton_parsing() {
     let input = r#"
   fn(): null {};
   fn(x: int): int {};
   fn(x: int, y: boolean, z: string): boolean {};
+  fn(x: float): float {};
+  fn(x: i8, y: u64): u16 {};
 "#;
     let program = parse_input(input);
     statement_assert(&program[0], "fn(): null {  }");
@@ -948,6 +1535,19 @@ fn funciton_parsing() {
         &program[2],
         "fn(x: int, y: boolean, z: string): boolean {  }",
     );
+    statement_assert(&program[3], "fn(x: float): float {  }");
+    statement_assert(&program[4], "fn(x: i8, y: u64): u16 {  }");
+}
+
+#[test]
+fn mutable_parameter_parsing() {
+    let input = r#"
+  fn(mut x: int): int {};
+  fn(x: int, mut y: boolean): boolean {};
+"#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "fn(mut x: int): int {  }");
+    statement_assert(&program[1], "fn(x: int, mut y: boolean): boolean {  }");
 }
 
 #[test]
@@ -966,11 +1566,57 @@ fn call_parsing() {
     statement_assert(&program[2], "add((((a + b) + ((c * d) / f)) + g))");
 }
 
+#[test]
+fn extern_parsing() {
+    let input = r#"
+  extern fn puts(s: string): int;
+  extern fn printf(format: string, ...): int;
+  extern fn abort(): null
+"#;
+    let program = parse_input(input);
+    statement_assert(&program[0], "extern fn puts(s: string): int");
+    statement_assert(&program[1], "extern fn printf(format: string, ...): int");
+    statement_assert(&program[2], "extern fn abort(): null");
+
+    let input = r#"
+  extern fn puts(s: string);
+"#;
+    parse_and_emit_error(
+        input,
+        vec!["expected next token to be Semicolon instead. row: 1, column: 27"],
+    );
+}
+
+#[test]
+fn export_parsing() {
+    let input = r#"
+  export fn add(a: int, b: int): int { return a + b; };
+  export fn reset(): null {}
+"#;
+    let program = parse_input(input);
+    statement_assert(
+        &program[0],
+        "export fn add(a: int, b: int): int { return (a + b) }",
+    );
+    statement_assert(&program[1], "export fn reset(): null {  }");
+
+    let input = r#"
+  export 1;
+"#;
+    parse_and_emit_error(
+        input,
+        vec!["expected next token to be Digit instead. row: 1, column: 9"],
+    );
+}
+
 #[test]
 fn wrong_prefix() {
     let input = r#"
 
     return > 3;
   "#;
-    parse_and_emit_error(input, vec!["no prefix parse function for Gt. row: 2"]);
+    parse_and_emit_error(
+        input,
+        vec!["no prefix parse function for Gt. row: 2, column: 11"],
+    );
 }
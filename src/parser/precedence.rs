@@ -25,6 +25,12 @@ lazy_static! {
     m.insert(TokenType::Multiply, Precedences::Product);
     m.insert(TokenType::Divide, Precedences::Product);
 
+    // **
+    m.insert(TokenType::Power, Precedences::Power);
+
+    // as
+    m.insert(TokenType::As, Precedences::Cast);
+
     m.insert(TokenType::Lparen, Precedences::Call);
     m
   };
@@ -38,6 +44,8 @@ pub enum Precedences {
     LessGrater, // >, >=, <, <=
     Sum,        // +, -
     Product,    // *, /
+    Power,      // **
+    Cast,       // X as Y
     Prefix,     // -X, !X
     Call,       // the_function(X)
 }
@@ -2,12 +2,45 @@ use parser::expressions::*;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Statement {
-    Let(Identifier, LLVMExpressionType, Expression),
-    Return(Expression),
+    Let(Identifier, Mutability, LLVMExpressionType, Expression),
+    Return(Expression, Location),
     Expression(Expression),
     While(Expression, BlockStatement),
-    Assignment(Identifier, Expression),
-    AssignmentAggregate(Identifier, Expression, Expression),
+    Assignment(Identifier, Expression, Location),
+    AssignmentAggregate(Identifier, Expression, Expression, Location),
+    Extern(ExternFunction),
+    Export(Identifier, Expression, Location),
+}
+
+// `extern fn puts(s: string): int;` declares a C function. `...` at the end makes it variadic
+#[derive(PartialEq, Clone, Debug)]
+pub struct ExternFunction {
+    pub name: Identifier,
+    pub parameters: Vec<Identifier>,
+    pub parameter_types: Vec<LLVMExpressionType>,
+    pub variadic: bool,
+    pub return_type: LLVMExpressionType,
+    pub location: Location,
+}
+
+impl ExternFunction {
+    pub fn string(&self) -> String {
+        let mut parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .zip(self.parameter_types.iter())
+            .map(|(name, parameter_type)| format!("{}: {}", name.0, parameter_type))
+            .collect();
+        if self.variadic {
+            parameters.push("...".to_string());
+        }
+        format!(
+            "extern fn {}({}): {}",
+            self.name.0,
+            parameters.join(", "),
+            self.return_type
+        )
+    }
 }
 
 pub type BlockStatement = Vec<Statement>;
@@ -17,12 +50,17 @@ impl Statement {
     #[allow(dead_code)]
     pub fn emit_debug_info(&self) -> String {
         match self {
-            Statement::Let(Identifier(ref string), _, expr) => write_string!(format!(
-                "[ identifiy: {}, expression: {} ]",
-                string,
-                expr.string()
-            )),
-            Statement::Return(expr) => write_string!(format!("[ expression: {} ]", expr.string())),
+            Statement::Let(Identifier(ref string), mutability, _, expr) => {
+                write_string!(format!(
+                    "[ identifiy: {}{}, expression: {} ]",
+                    mutability,
+                    string,
+                    expr.string()
+                ))
+            }
+            Statement::Return(expr, _) => {
+                write_string!(format!("[ expression: {} ]", expr.string()))
+            }
             Statement::Expression(expr) => {
                 write_string!(format!("[ expression: {} ]", expr.string()))
             }
@@ -41,12 +79,12 @@ impl Statement {
                     ret_string
                 ))
             }
-            Statement::Assignment(Identifier(ref string), expr) => write_string!(format!(
+            Statement::Assignment(Identifier(ref string), expr, _) => write_string!(format!(
                 "[ identifiy: {}, expression: {} ]",
                 string,
                 expr.string()
             )),
-            Statement::AssignmentAggregate(Identifier(ref string), assign_expr, index_expr) => {
+            Statement::AssignmentAggregate(Identifier(ref string), assign_expr, index_expr, _) => {
                 write_string!(format!(
                     "[ identifiy: {}, expression: {}, idnex: {} ]",
                     string,
@@ -54,15 +92,19 @@ impl Statement {
                     index_expr.string()
                 ))
             }
+            Statement::Extern(extern_function) => {
+                write_string!(format!("[ extern: {} ]", extern_function.string()))
+            }
+            Statement::Export(_, _, _) => write_string!(format!("[ {} ]", self.string())),
         }
     }
 
     pub fn string(&self) -> String {
         match self {
-            Statement::Let(Identifier(ref string), _, expr) => {
-                format!("let {} = {}", string, &expr.string())
+            Statement::Let(Identifier(ref string), mutability, _, expr) => {
+                format!("let {}{} = {}", mutability, string, &expr.string())
             }
-            Statement::Return(expr) => ("return ".to_owned() + &expr.string()).to_string(),
+            Statement::Return(expr, _) => ("return ".to_owned() + &expr.string()).to_string(),
             Statement::Expression(expr) => expr.string(),
             Statement::While(expr, body) => {
                 let mut ret_string = String::new();
@@ -75,10 +117,10 @@ impl Statement {
                 }
                 format!("while ({}) {{ {} }}", expr.string(), ret_string)
             }
-            Statement::Assignment(Identifier(ref string), expr) => {
+            Statement::Assignment(Identifier(ref string), expr, _) => {
                 format!("{} = {}", string, &expr.string())
             }
-            Statement::AssignmentAggregate(Identifier(ref string), assign_expr, index_expr) => {
+            Statement::AssignmentAggregate(Identifier(ref string), assign_expr, index_expr, _) => {
                 format!(
                     "{}[{}] = {}",
                     string,
@@ -86,6 +128,15 @@ impl Statement {
                     &assign_expr.string()
                 )
             }
+            Statement::Extern(extern_function) => extern_function.string(),
+            Statement::Export(Identifier(ref string), function, _) => {
+                format!(
+                    "export {}",
+                    function
+                        .string()
+                        .replacen("fn", &format!("fn {}", string), 1)
+                )
+            }
         }
     }
 }